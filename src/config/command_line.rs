@@ -0,0 +1,370 @@
+use anyhow::{bail, Context, Error, Result};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{fmt, process::ExitStatus, str};
+
+/// A command, which is either executed by the shell, or directly as a program
+/// with its arguments.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "CommandLineToml")]
+pub enum CommandLine {
+    /// A command line that is passed to the shell.
+    Shell(String),
+    /// A program and its arguments, which are executed without a shell.
+    /// Written as an array of strings, e.g. `["rm", "--", "$line"]`.
+    Argv(Vec<String>),
+}
+
+/// The command of an operation that executes a subcommand, along with the
+/// options it was given, in the format
+/// `[--argv] [--ok-exit-codes CODES] -- CMD`. With `--argv`, the command is an
+/// array of strings that is executed without a shell.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct SubcommandLine {
+    pub command: CommandLine,
+    /// The exit codes with which the subcommand is successful, which
+    /// override those of its keybinding and the global ones.
+    pub ok_exit_codes: Option<OkExitCodes>,
+}
+
+/// The command of an operation that executes a subcommand in the background,
+/// in the format `[--argv] -- CMD`. Unlike [`SubcommandLine`], it accepts no
+/// `--ok-exit-codes`, because the exit status of background commands is never
+/// checked.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct NonBlockingSubcommandLine(pub CommandLine);
+
+/// The shell and its arguments, which shell commands are appended to as the
+/// last argument, e.g. `["sh", "-c"]`.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(try_from = "Vec<String>")]
+pub struct Shell(Vec<String>);
+
+/// The exit codes with which a command is treated as successful, e.g. `[0, 1]`
+/// for `grep`, which exits with 1 if nothing matches.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "Vec<i32>")]
+pub struct OkExitCodes(Vec<i32>);
+
+impl CommandLine {
+    /// Get the program and its arguments that execute this command, either
+    /// with the `shell`, or directly. The arguments of argv commands are
+    /// passed through `expand`, since there is no shell that would expand
+    /// environment variables in them, and may expand to multiple arguments.
+    pub fn argv(&self, shell: &Shell, expand: impl Fn(&str) -> Vec<String>) -> Vec<String> {
+        match self {
+            Self::Shell(command) => shell.0.iter().cloned().chain([command.clone()]).collect(),
+            Self::Argv(argv) => argv.iter().flat_map(|arg| expand(arg)).collect(),
+        }
+    }
+
+    /// Parse an argv command written as an array of strings, e.g.
+    /// `["rm", "--", "$line"]`.
+    fn parse_argv(s: &str) -> Result<Self> {
+        /// Parsing a TOML key-value pair is the easiest way to parse a TOML
+        /// array.
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct ArgvToml {
+            argv: Vec<String>,
+        }
+
+        let ArgvToml { argv } = toml::from_str(&format!("argv = {}", s))
+            .with_context(|| format!("Invalid array of strings \"{}\"", s))?;
+        argv.try_into()
+    }
+}
+
+impl Default for CommandLine {
+    fn default() -> Self {
+        Self::Shell(String::new())
+    }
+}
+
+impl str::FromStr for SubcommandLine {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (options, command) = match s.strip_prefix("-- ") {
+            Some(command) => ("", command),
+            None => s.split_once(" -- ").with_context(|| {
+                format!(
+                    "Invalid subcommand, expected \"[--argv] [--ok-exit-codes CODES] -- CMD\": {}",
+                    s
+                )
+            })?,
+        };
+
+        let mut argv = false;
+        let mut ok_exit_codes = None;
+        let mut options = options.split_whitespace();
+        while let Some(option) = options.next() {
+            match option {
+                "--argv" => argv = true,
+                "--ok-exit-codes" => {
+                    let codes = options
+                        .next()
+                        .with_context(|| format!("Missing exit codes in: {}", s))?;
+                    ok_exit_codes = Some(codes.parse()?);
+                }
+                _ => bail!("Invalid subcommand option \"{}\" in: {}", option, s),
+            }
+        }
+
+        let command = if argv {
+            CommandLine::parse_argv(command)?
+        } else {
+            CommandLine::Shell(command.to_owned())
+        };
+        Ok(Self {
+            command,
+            ok_exit_codes,
+        })
+    }
+}
+
+impl fmt::Display for SubcommandLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let CommandLine::Argv(_) = self.command {
+            write!(f, "--argv ")?;
+        }
+        if let Some(ok_exit_codes) = &self.ok_exit_codes {
+            write!(f, "--ok-exit-codes {} ", ok_exit_codes)?;
+        }
+        write!(f, "-- {}", self.command)
+    }
+}
+
+impl str::FromStr for NonBlockingSubcommandLine {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let SubcommandLine {
+            command,
+            ok_exit_codes,
+        } = s.parse()?;
+        if ok_exit_codes.is_some() {
+            bail!(
+                "Background commands don't support \"--ok-exit-codes\", because their exit status is never checked: {}",
+                s
+            );
+        }
+        Ok(Self(command))
+    }
+}
+
+impl fmt::Display for NonBlockingSubcommandLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let CommandLine::Argv(_) = self.0 {
+            write!(f, "--argv ")?;
+        }
+        write!(f, "-- {}", self.0)
+    }
+}
+
+impl TryFrom<Vec<String>> for CommandLine {
+    type Error = Error;
+    fn try_from(argv: Vec<String>) -> Result<Self, Self::Error> {
+        if argv.is_empty() {
+            bail!("An argv command must contain at least the program to execute");
+        }
+        Ok(Self::Argv(argv))
+    }
+}
+
+/// A command in the TOML config file, which is either a string or an array of
+/// strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CommandLineToml {
+    String(String),
+    Array(Vec<String>),
+}
+
+impl TryFrom<CommandLineToml> for CommandLine {
+    type Error = Error;
+    fn try_from(value: CommandLineToml) -> Result<Self, Self::Error> {
+        match value {
+            CommandLineToml::String(command) => Ok(CommandLine::Shell(command)),
+            CommandLineToml::Array(argv) => argv.try_into(),
+        }
+    }
+}
+
+impl fmt::Display for CommandLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shell(command) => write!(f, "{}", command),
+            Self::Argv(argv) => {
+                write!(f, "[")?;
+                for (i, arg) in argv.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_toml_string(f, arg)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Write `s` as a quoted TOML basic string.
+fn write_toml_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\t' => write!(f, "\\t")?,
+            '\r' => write!(f, "\\r")?,
+            c if c.is_control() => write!(f, "\\u{:04X}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl str::FromStr for Shell {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+            .try_into()
+            .with_context(|| format!("Invalid shell \"{}\"", s))
+    }
+}
+
+impl TryFrom<Vec<String>> for Shell {
+    type Error = Error;
+    fn try_from(shell: Vec<String>) -> Result<Self, Self::Error> {
+        if shell.is_empty() {
+            bail!("The shell must contain at least the program to execute");
+        }
+        Ok(Self(shell))
+    }
+}
+
+impl OkExitCodes {
+    /// Whether a command that exited with `exit_status` was successful.
+    pub fn is_ok(&self, exit_status: ExitStatus) -> bool {
+        exit_status
+            .code()
+            .is_some_and(|code| self.0.contains(&code))
+    }
+}
+
+impl Default for OkExitCodes {
+    fn default() -> Self {
+        Self(vec![0])
+    }
+}
+
+impl fmt::Display for OkExitCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().join(","))
+    }
+}
+
+impl str::FromStr for OkExitCodes {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|code| code.trim().parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::from)
+            .and_then(Self::try_from)
+            .with_context(|| format!("Invalid exit codes \"{}\"", s))
+    }
+}
+
+impl TryFrom<Vec<i32>> for OkExitCodes {
+    type Error = Error;
+    fn try_from(codes: Vec<i32>) -> Result<Self, Self::Error> {
+        if codes.is_empty() {
+            bail!("At least one exit code must be treated as success");
+        }
+        Ok(Self(codes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subcommand_line() {
+        let command = |s: &str| s.parse::<SubcommandLine>().map(|line| line.command);
+
+        assert_eq!(
+            command("-- ls -l | grep foo").unwrap(),
+            CommandLine::Shell("ls -l | grep foo".to_owned())
+        );
+        assert_eq!(
+            command(r#"-- [ "$line" ] && cat "$line""#).unwrap(),
+            CommandLine::Shell(r#"[ "$line" ] && cat "$line""#.to_owned())
+        );
+        assert_eq!(
+            command(r#"--argv -- ["rm", "--", "$line"]"#).unwrap(),
+            CommandLine::Argv(vec!["rm".to_owned(), "--".to_owned(), "$line".to_owned()])
+        );
+        assert!(command("--argv -- []").is_err());
+        assert!(command("--argv -- rm $line").is_err());
+        assert!(command("--unknown -- ls").is_err());
+        assert!(command("ls").is_err());
+
+        let grep: SubcommandLine = "--ok-exit-codes 0,1 -- grep foo".parse().unwrap();
+        assert_eq!(grep.ok_exit_codes, Some(OkExitCodes(vec![0, 1])));
+        assert!(command("--ok-exit-codes -- grep foo").is_err());
+
+        // Displaying and parsing again results in the same command.
+        let argv = SubcommandLine {
+            command: CommandLine::Argv(vec![
+                "echo".to_owned(),
+                "a \"quoted\"\\\tstring".to_owned(),
+            ]),
+            ok_exit_codes: Some(OkExitCodes(vec![0, 2])),
+        };
+        assert_eq!(argv.to_string().parse::<SubcommandLine>().unwrap(), argv);
+    }
+
+    #[test]
+    fn test_parse_non_blocking_subcommand_line() {
+        assert_eq!(
+            "--argv -- [\"ls\"]"
+                .parse::<NonBlockingSubcommandLine>()
+                .unwrap(),
+            NonBlockingSubcommandLine(CommandLine::Argv(vec!["ls".to_owned()]))
+        );
+        assert!("--ok-exit-codes 0,1 -- grep foo"
+            .parse::<NonBlockingSubcommandLine>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_command_line_argv() {
+        let shell: Shell = "bash -euo pipefail -c".parse().unwrap();
+        let expand = |arg: &str| vec![arg.replace("$line", "my file")];
+
+        assert_eq!(
+            CommandLine::Shell("echo $line".to_owned()).argv(&shell, expand),
+            ["bash", "-euo", "pipefail", "-c", "echo $line"]
+        );
+        assert_eq!(
+            CommandLine::Argv(vec!["echo".to_owned(), "$line".to_owned()]).argv(&shell, expand),
+            ["echo", "my file"]
+        );
+        assert!("".parse::<Shell>().is_err());
+    }
+
+    #[test]
+    fn test_parse_ok_exit_codes() {
+        assert_eq!(
+            "0, 1".parse::<OkExitCodes>().unwrap(),
+            OkExitCodes(vec![0, 1])
+        );
+        assert!("".parse::<OkExitCodes>().is_err());
+        assert!("0,one".parse::<OkExitCodes>().is_err());
+    }
+}
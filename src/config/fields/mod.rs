@@ -30,6 +30,19 @@ impl Fields {
             selections,
         })
     }
+
+    /// Get the field of the `line` at the 1-based `number`, where fields are
+    /// separated by the field separator, or by whitespace if there is none.
+    pub fn get_field<'a>(&self, line: &'a str, number: usize) -> Option<&'a str> {
+        let index = number.checked_sub(1)?;
+        match &self.separator {
+            Some(separator) => {
+                let separator: &String = separator.as_ref();
+                line.split(separator).nth(index)
+            }
+            None => line.split_whitespace().nth(index),
+        }
+    }
 }
 
 pub trait TableFormatter {
@@ -75,3 +88,19 @@ impl TableFormatter for &str {
         Ok(table)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_field() {
+        let fields = Fields::try_new(Some(",".parse().unwrap()), None).unwrap();
+        assert_eq!(fields.get_field("a,b c,d", 2), Some("b c"));
+        assert_eq!(fields.get_field("a,b c,d", 4), None);
+        assert_eq!(fields.get_field("a,b c,d", 0), None);
+
+        let fields = Fields::try_new(None, None).unwrap();
+        assert_eq!(fields.get_field("a  b\tc", 3), Some("c"));
+    }
+}
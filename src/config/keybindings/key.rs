@@ -1,15 +1,20 @@
 use anyhow::{bail, Context, Error, Result};
 use crossterm::event::{
     KeyCode as CrosstermKeyCode, KeyEvent as CrosstermKeyEvent,
-    KeyModifiers as CrosstermKeyModifiers,
+    KeyModifiers as CrosstermKeyModifiers, MouseButton as CrosstermMouseButton,
+    MouseEvent as CrosstermMouseEvent, MouseEventKind as CrosstermMouseEventKind,
 };
+use derive_more::From;
 use derive_new::new;
+use itertools::Itertools;
 use parse_display::{Display, FromStr};
+use serde::Deserialize;
 use std::{fmt, str};
 use strum::{EnumIter, EnumMessage, EnumProperty};
 
 /// The specific combinations of modifiers and key codes that we allow/handle.
-#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Debug, new)]
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Debug, new, Deserialize)]
+#[serde(try_from = "String")]
 pub struct KeyEvent {
     modifier: KeyModifier,
     code: KeyCode,
@@ -80,6 +85,13 @@ pub enum KeyCode {
     Tab,
     Space,
 
+    // Mouse buttons and the mouse wheel
+    LeftClick,
+    RightClick,
+    MiddleClick,
+    ScrollUp,
+    ScrollDown,
+
     #[display("{0}")]
     #[strum(message = "<lowercase char>, <uppercase char>")]
     Char(char),
@@ -91,6 +103,18 @@ pub enum KeyCode {
     F(u8),
 }
 
+impl KeyEvent {
+    /// Get the key modifier.
+    pub fn modifier(&self) -> &KeyModifier {
+        &self.modifier
+    }
+
+    /// Get the key code.
+    pub fn code(&self) -> &KeyCode {
+        &self.code
+    }
+}
+
 impl str::FromStr for KeyEvent {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -112,6 +136,13 @@ impl str::FromStr for KeyEvent {
     }
 }
 
+impl TryFrom<String> for KeyEvent {
+    type Error = Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 impl fmt::Display for KeyEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.modifier {
@@ -122,6 +153,53 @@ impl fmt::Display for KeyEvent {
     }
 }
 
+/// A sequence of key events that have to be pressed one after the other,
+/// e.g. `g g`.
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Debug, From)]
+pub struct KeySequence(Vec<KeyEvent>);
+
+impl KeySequence {
+    /// Get the key events in the order they have to be pressed.
+    pub fn keys(&self) -> &[KeyEvent] {
+        &self.0
+    }
+
+    /// Get all sequences that are a strict prefix of this sequence, e.g. `g`
+    /// for `g g`.
+    pub fn strict_prefixes(&self) -> impl Iterator<Item = KeySequence> + '_ {
+        (1..self.0.len()).map(|len| KeySequence(self.0[..len].to_vec()))
+    }
+}
+
+impl From<KeyEvent> for KeySequence {
+    fn from(key: KeyEvent) -> Self {
+        Self(vec![key])
+    }
+}
+
+impl str::FromStr for KeySequence {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let keys: Vec<KeyEvent> = s
+            .split_whitespace()
+            .map(|key| {
+                key.parse()
+                    .with_context(|| format!("Invalid KeyEvent: {}", key))
+            })
+            .collect::<Result<_>>()?;
+        if keys.is_empty() {
+            bail!("Key sequence must contain at least one key");
+        }
+        Ok(Self(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().join(" "))
+    }
+}
+
 impl TryFrom<CrosstermKeyEvent> for KeyEvent {
     type Error = Error;
     fn try_from(key: CrosstermKeyEvent) -> std::result::Result<Self, Self::Error> {
@@ -142,6 +220,24 @@ impl TryFrom<CrosstermKeyEvent> for KeyEvent {
     }
 }
 
+impl KeyEvent {
+    /// Convert a mouse event into the key event of a mouse button or the
+    /// mouse wheel, or `None` if it can't be bound, e.g. a mouse movement.
+    pub fn from_mouse_event(mouse: CrosstermMouseEvent) -> Option<Self> {
+        let code = match mouse.kind {
+            CrosstermMouseEventKind::Down(CrosstermMouseButton::Left) => KeyCode::LeftClick,
+            CrosstermMouseEventKind::Down(CrosstermMouseButton::Right) => KeyCode::RightClick,
+            CrosstermMouseEventKind::Down(CrosstermMouseButton::Middle) => KeyCode::MiddleClick,
+            CrosstermMouseEventKind::ScrollUp => KeyCode::ScrollUp,
+            CrosstermMouseEventKind::ScrollDown => KeyCode::ScrollDown,
+            _ => return None,
+        };
+        let modifier = mouse.modifiers.try_into().ok()?;
+
+        Some(Self { modifier, code })
+    }
+}
+
 impl TryFrom<CrosstermKeyModifiers> for KeyModifier {
     type Error = Error;
     fn try_from(value: CrosstermKeyModifiers) -> std::result::Result<Self, Self::Error> {
@@ -254,6 +350,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_valid_key_sequences() {
+        assert_eq_parse_display(
+            "g g",
+            KeySequence(vec![
+                KeyEvent::new(KeyModifier::None, KeyCode::Char('g')),
+                KeyEvent::new(KeyModifier::None, KeyCode::Char('g')),
+            ]),
+        );
+
+        assert_eq_parse_display(
+            "space ctrl+f d",
+            KeySequence(vec![
+                KeyEvent::new(KeyModifier::None, KeyCode::Space),
+                KeyEvent::new(KeyModifier::Ctrl, KeyCode::Char('f')),
+                KeyEvent::new(KeyModifier::None, KeyCode::Char('d')),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_mouse_events() {
+        assert_eq_parse_display(
+            "ctrl+leftclick",
+            KeyEvent::new(KeyModifier::Ctrl, KeyCode::LeftClick),
+        );
+
+        let mouse_event = |kind| CrosstermMouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers: CrosstermKeyModifiers::NONE,
+        };
+        assert_eq!(
+            KeyEvent::from_mouse_event(mouse_event(CrosstermMouseEventKind::ScrollDown)),
+            Some(KeyEvent::new(KeyModifier::None, KeyCode::ScrollDown))
+        );
+        assert!(KeyEvent::from_mouse_event(mouse_event(CrosstermMouseEventKind::Moved)).is_none());
+    }
+
+    #[test]
+    fn test_invalid_key_sequences() {
+        assert!("".parse::<KeySequence>().is_err());
+        assert!("g foo".parse::<KeySequence>().is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_invalid_modifiers() {
@@ -1,60 +1,209 @@
 mod help_menu_format;
 mod key;
+mod modes;
 mod operations;
 
 use anyhow::{bail, Context, Error, Result};
 use derive_more::From;
 use serde::Deserialize;
 use std::sync::Arc;
-use std::{collections::HashMap, fmt, str};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, str,
+    time::Duration,
+};
 use tokio::sync::Mutex;
 
 use super::table::Table;
+use crate::config::{OkExitCodes, Shell};
 use crate::ui::EnvVariables;
 
 pub use self::help_menu_format::{KeybindingsHelpMenuColumn, KeybindingsHelpMenuFormat};
-pub use self::key::{KeyCode, KeyEvent, KeyModifier};
-pub use self::operations::{OperationExecutable, OperationParsed, Operations, OperationsParsed};
+pub use self::key::{KeyCode, KeyEvent, KeyModifier, KeySequence};
+pub use self::modes::{ModeName, ModesParsed, ModesToml};
+pub use self::operations::{
+    IntervalChange, LinePattern, OperationExecutable, OperationParsed, Operations, OperationsParsed,
+};
+
+pub struct Keybindings {
+    keybindings: HashMap<KeySequence, Operations>,
+    /// All key sequences that are a strict prefix of some key sequence in
+    /// `keybindings`.
+    prefixes: HashSet<KeySequence>,
+}
 
-pub struct Keybindings(HashMap<KeyEvent, Operations>);
+/// How a sequence of pressed keys relates to the key sequences of all
+/// keybindings.
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeySequenceMatch {
+    /// The keys are a keybinding's key sequence, and can't be continued to
+    /// form another keybinding's key sequence.
+    Complete,
+    /// The keys are a keybinding's key sequence, but can also be continued to
+    /// form another keybinding's key sequence.
+    Ambiguous,
+    /// The keys can be continued to form a keybinding's key sequence.
+    Partial,
+    /// The keys neither are nor can be continued to form a keybinding's key
+    /// sequence.
+    NoMatch,
+}
 
 impl Keybindings {
-    pub fn get_operations(&self, key: &KeyEvent) -> Option<&Operations> {
-        self.0.get(key)
+    pub fn get_operations(&self, key_sequence: &KeySequence) -> Option<&Operations> {
+        self.keybindings.get(key_sequence)
+    }
+
+    /// Check how the pressed `key_sequence` matches the key sequences of all
+    /// keybindings.
+    pub fn match_key_sequence(&self, key_sequence: &KeySequence) -> KeySequenceMatch {
+        match (
+            self.keybindings.contains_key(key_sequence),
+            self.prefixes.contains(key_sequence),
+        ) {
+            (true, false) => KeySequenceMatch::Complete,
+            (true, true) => KeySequenceMatch::Ambiguous,
+            (false, true) => KeySequenceMatch::Partial,
+            (false, false) => KeySequenceMatch::NoMatch,
+        }
     }
 
+    /// The `timeout` and `ok_exit_codes` apply to the subcommands of
+    /// keybindings that don't specify their own.
     pub fn from_parsed(
         keybindings_parsed: KeybindingsParsed,
         env_variables: &Arc<Mutex<EnvVariables>>,
+        shell: &Shell,
+        timeout: Option<Duration>,
+        ok_exit_codes: &OkExitCodes,
     ) -> Self {
-        Self(
-            keybindings_parsed
-                .0
-                .into_iter()
-                .map(|(key, (ops, _desc))| (key, Operations::from_parsed(ops, env_variables)))
-                .collect(),
-        )
+        let prefixes = keybindings_parsed
+            .0
+            .keys()
+            .flat_map(KeySequence::strict_prefixes)
+            .collect();
+        let keybindings = keybindings_parsed
+            .0
+            .into_iter()
+            .map(|(key, keybinding)| {
+                let timeout = keybinding.timeout.or(timeout);
+                let ok_exit_codes = keybinding.ok_exit_codes.as_ref().unwrap_or(ok_exit_codes);
+                let ops = Operations::from_parsed(
+                    keybinding.operations,
+                    env_variables,
+                    shell,
+                    timeout,
+                    ok_exit_codes,
+                );
+                (key, ops)
+            })
+            .collect();
+        Self {
+            keybindings,
+            prefixes,
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, From)]
-pub struct KeybindingsParsed(HashMap<KeyEvent, (OperationsParsed, Description)>);
+/// The keybindings of the default mode and of all user-defined modes. The
+/// keybindings of a user-defined mode take precedence over those of the
+/// default mode, but all keys not bound in a user-defined mode fall back to
+/// the keybindings of the default mode.
+pub struct ModalKeybindings {
+    default: Keybindings,
+    modes: HashMap<ModeName, Keybindings>,
+}
+
+impl ModalKeybindings {
+    /// Get the keybindings that are active in `mode`, where `None` is the
+    /// default mode.
+    pub fn get(&self, mode: Option<&ModeName>) -> &Keybindings {
+        mode.and_then(|mode| self.modes.get(mode))
+            .unwrap_or(&self.default)
+    }
+
+    pub fn from_parsed(
+        keybindings_parsed: KeybindingsParsed,
+        modes_parsed: ModesParsed,
+        env_variables: &Arc<Mutex<EnvVariables>>,
+        shell: &Shell,
+        timeout: Option<Duration>,
+        ok_exit_codes: &OkExitCodes,
+    ) -> Self {
+        let modes = modes_parsed
+            .into_iter()
+            .map(|(name, mode_keybindings_parsed)| {
+                let merged = KeybindingsParsed::merge(
+                    Some(mode_keybindings_parsed),
+                    Some(keybindings_parsed.clone()),
+                )
+                .expect("merge should not be empty given both inputs are some");
+                (
+                    name,
+                    Keybindings::from_parsed(merged, env_variables, shell, timeout, ok_exit_codes),
+                )
+            })
+            .collect();
+        Self {
+            default: Keybindings::from_parsed(
+                keybindings_parsed,
+                env_variables,
+                shell,
+                timeout,
+                ok_exit_codes,
+            ),
+            modes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, From)]
+pub struct KeybindingsParsed(HashMap<KeySequence, KeybindingParsed>);
+
+/// The operations of a keybinding, along with its description and the
+/// settings of its subcommands that override the global ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeybindingParsed {
+    operations: OperationsParsed,
+    description: Description,
+    timeout: Option<Duration>,
+    ok_exit_codes: Option<OkExitCodes>,
+}
+
+/// A keybinding without `Description`, timeout and ok exit codes.
+impl From<OperationsParsed> for KeybindingParsed {
+    fn from(operations: OperationsParsed) -> Self {
+        Self {
+            operations,
+            description: Description::default(),
+            timeout: None,
+            ok_exit_codes: None,
+        }
+    }
+}
 
 // TODO: should be generated by some crate
 
-/// Allow initialization without `Description`.
-impl From<HashMap<KeyEvent, OperationsParsed>> for KeybindingsParsed {
-    fn from(value: HashMap<KeyEvent, OperationsParsed>) -> Self {
+/// Allow initialization without `Description`, timeout and ok exit codes.
+impl From<HashMap<KeySequence, OperationsParsed>> for KeybindingsParsed {
+    fn from(value: HashMap<KeySequence, OperationsParsed>) -> Self {
         Self(
             value
                 .into_iter()
-                .map(|(key, ops)| (key, (ops, Description::default())))
+                .map(|(key, ops)| (key, ops.into()))
                 .collect(),
         )
     }
 }
 
 impl KeybindingsParsed {
+    /// Iterate over the operations of all keybindings.
+    pub fn operations(&self) -> impl Iterator<Item = &OperationParsed> {
+        self.0
+            .values()
+            .flat_map(|keybinding| &keybinding.operations)
+    }
+
     /// Merge two keybinding hashmaps, where a value is taken from `opt_a` over
     /// `opt_b` on identical keys.
     pub fn merge(opt_a: Option<Self>, opt_b: Option<Self>) -> Option<Self> {
@@ -89,10 +238,13 @@ impl KeybindingsPrintable {
         let keybindings_printable = keybindings_parsed
             .0
             .into_iter()
-            .map(|(key, (operations, description))| {
+            .map(|(key, keybinding)| {
                 (
                     key.to_string(),
-                    (operations.to_string(), description.to_string()),
+                    (
+                        keybinding.operations.to_string(),
+                        keybinding.description.to_string(),
+                    ),
                 )
             })
             .collect();
@@ -148,20 +300,31 @@ impl TryFrom<KeybindingsToml> for KeybindingsParsed {
             .0
             .into_iter()
             .map(|(key, operations)| {
-                let (operations, description) = match operations {
+                let (operations, description, timeout, ok_exit_codes) = match operations {
                     TomlOperationsWithDescription::OpsOnly(operations) => {
-                        (operations, Description::default())
+                        (operations, None, None, None)
                     }
                     TomlOperationsWithDescription::OpsWithDesc {
                         operations,
                         description,
-                    } => (operations, Description(description)),
+                        timeout,
+                        ok_exit_codes,
+                    } => (operations, description, timeout, ok_exit_codes),
                 };
+                let timeout = timeout
+                    .map(Duration::try_from_secs_f64)
+                    .transpose()
+                    .with_context(|| format!("Invalid timeout of keybinding {}", key.0))?;
                 Ok((
                     key.0
                         .parse()
-                        .with_context(|| format!("Invalid key event: {}", key.0))?,
-                    (Vec::<String>::from(operations).try_into()?, description),
+                        .with_context(|| format!("Invalid key sequence: {}", key.0))?,
+                    KeybindingParsed {
+                        operations: Vec::<String>::from(operations).try_into()?,
+                        description: Description(description),
+                        timeout,
+                        ok_exit_codes,
+                    },
                 ))
             })
             .collect::<Result<_>>()?;
@@ -180,6 +343,13 @@ pub enum TomlOperationsWithDescription {
     OpsWithDesc {
         operations: TomlOperations,
         description: Option<String>,
+        /// Seconds (f64) after which the blocking subcommands of the
+        /// keybinding are killed.
+        timeout: Option<f64>,
+        /// The exit codes with which the blocking subcommands of the
+        /// keybinding are successful.
+        #[serde(rename = "ok-exit-codes")]
+        ok_exit_codes: Option<OkExitCodes>,
     },
 }
 
@@ -229,12 +399,10 @@ impl TryFrom<KeybindingsCli> for KeybindingsParsed {
             .map(|KeybindingCli { key, operations }| {
                 Ok((
                     key.parse()
-                        .with_context(|| format!("Invalid key event: {}", key))?,
-                    (
-                        operations.try_into()?,
-                        // Descriptions are not available in `KeybindingsCli`.
-                        Description::default(),
-                    ),
+                        .with_context(|| format!("Invalid key sequence: {}", key))?,
+                    // Descriptions, timeouts and ok exit codes are not
+                    // available in `KeybindingsCli`.
+                    OperationsParsed::try_from(operations)?.into(),
                 ))
             })
             .collect::<Result<_>>()?;
@@ -272,17 +440,26 @@ impl str::FromStr for KeybindingCli {
 mod tests {
     use super::*;
     use indoc::indoc;
+    use itertools::Itertools;
 
     #[test]
     fn test_merge_keybindings() {
-        let k1 = KeyEvent::new(KeyModifier::None, KeyCode::BackTab);
-        let k2 = KeyEvent::new(KeyModifier::None, KeyCode::Backspace);
-        let k3 = KeyEvent::new(KeyModifier::None, KeyCode::Delete);
-
-        let v1 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking("v1".to_string())]);
-        let v2 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking("v2".to_string())]);
-        let v3 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking("v3".to_string())]);
-        let v4 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking("v4".to_string())]);
+        let k1 = KeySequence::from(KeyEvent::new(KeyModifier::None, KeyCode::BackTab));
+        let k2 = KeySequence::from(KeyEvent::new(KeyModifier::None, KeyCode::Backspace));
+        let k3 = KeySequence::from(KeyEvent::new(KeyModifier::None, KeyCode::Delete));
+
+        let v1 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking(
+            "-- v1".parse().unwrap(),
+        )]);
+        let v2 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking(
+            "-- v2".parse().unwrap(),
+        )]);
+        let v3 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking(
+            "-- v3".parse().unwrap(),
+        )]);
+        let v4 = OperationsParsed::from(vec![OperationParsed::ExecuteBlocking(
+            "-- v4".parse().unwrap(),
+        )]);
 
         let a: KeybindingsParsed = HashMap::from([(k1.clone(), v1), (k3.clone(), v4)]).into();
         let b: KeybindingsParsed = HashMap::from([(k1.clone(), v2), (k2.clone(), v3)]).into();
@@ -355,6 +532,8 @@ mod tests {
             TomlOperationsWithDescription::OpsWithDesc {
                 operations: TomlOperations::Multiple(vec!["op 1".to_string(), "op 2".to_string()]),
                 description: None,
+                timeout: None,
+                ok_exit_codes: None,
             },
             toml::from_str::<OperationsTomlWrapper>(ops_without_desc)
                 .unwrap()
@@ -366,12 +545,40 @@ mod tests {
         assert_eq!(
             TomlOperationsWithDescription::OpsWithDesc {
                 operations: TomlOperations::Multiple(vec!["op 1".to_string(), "op 2".to_string()]),
-                description: Some("A custom description".to_string())
+                description: Some("A custom description".to_string()),
+                timeout: None,
+                ok_exit_codes: None,
             },
             toml::from_str::<OperationsTomlWrapper>(long_with_desc)
                 .unwrap()
                 .foo
         );
+
+        let with_timeout = r#"foo = { operations = "op", timeout = 2.5 }"#;
+        assert_eq!(
+            TomlOperationsWithDescription::OpsWithDesc {
+                operations: TomlOperations::Single("op".to_string()),
+                description: None,
+                timeout: Some(2.5),
+                ok_exit_codes: None,
+            },
+            toml::from_str::<OperationsTomlWrapper>(with_timeout)
+                .unwrap()
+                .foo
+        );
+
+        let with_ok_exit_codes = r#"foo = { operations = "op", ok-exit-codes = [ 0, 1 ] }"#;
+        assert_eq!(
+            TomlOperationsWithDescription::OpsWithDesc {
+                operations: TomlOperations::Single("op".to_string()),
+                description: None,
+                timeout: None,
+                ok_exit_codes: Some("0,1".parse().unwrap()),
+            },
+            toml::from_str::<OperationsTomlWrapper>(with_ok_exit_codes)
+                .unwrap()
+                .foo
+        );
     }
 
     #[test]
@@ -401,6 +608,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_match_key_sequence() {
+        let keybindings_parsed: HashMap<KeySequence, OperationsParsed> = HashMap::from([
+            ("g".parse().unwrap(), vec![OperationParsed::Exit].into()),
+            ("g g".parse().unwrap(), vec![OperationParsed::Exit].into()),
+            (
+                "space f d".parse().unwrap(),
+                vec![OperationParsed::Exit].into(),
+            ),
+        ]);
+        let keybindings = Keybindings::from_parsed(
+            keybindings_parsed.into(),
+            &Arc::new(Mutex::new(EnvVariables::new())),
+            &"sh -c".parse().unwrap(),
+            None,
+            &OkExitCodes::default(),
+        );
+
+        let match_str =
+            |key_sequence: &str| keybindings.match_key_sequence(&key_sequence.parse().unwrap());
+
+        assert_eq!(match_str("g"), KeySequenceMatch::Ambiguous);
+        assert_eq!(match_str("g g"), KeySequenceMatch::Complete);
+        assert_eq!(match_str("space"), KeySequenceMatch::Partial);
+        assert_eq!(match_str("space f"), KeySequenceMatch::Partial);
+        assert_eq!(match_str("space f d"), KeySequenceMatch::Complete);
+        assert_eq!(match_str("space g"), KeySequenceMatch::NoMatch);
+        assert_eq!(match_str("g g g"), KeySequenceMatch::NoMatch);
+    }
+
+    #[test]
+    fn test_mode_keybindings_fall_back_to_default_mode() {
+        let keybindings_parsed: HashMap<KeySequence, OperationsParsed> = HashMap::from([
+            ("q".parse().unwrap(), vec![OperationParsed::Exit].into()),
+            (
+                "j".parse().unwrap(),
+                vec![OperationParsed::MoveCursorDown(1)].into(),
+            ),
+        ]);
+        let visual: ModeName = "visual".parse().unwrap();
+        let visual_keybindings_parsed: HashMap<KeySequence, OperationsParsed> = HashMap::from([(
+            "j".parse().unwrap(),
+            vec![OperationParsed::SelectLine].into(),
+        )]);
+        let modes_parsed: ModesParsed =
+            HashMap::from([(visual.clone(), visual_keybindings_parsed.into())]).into();
+        let keybindings = ModalKeybindings::from_parsed(
+            keybindings_parsed.into(),
+            modes_parsed,
+            &Arc::new(Mutex::new(EnvVariables::new())),
+            &"sh -c".parse().unwrap(),
+            None,
+            &OkExitCodes::default(),
+        );
+
+        let operations_str = |mode: Option<&ModeName>, key: &str| {
+            keybindings
+                .get(mode)
+                .get_operations(&key.parse().unwrap())
+                .map(|ops| ops.into_iter().map(ToString::to_string).join("+"))
+        };
+
+        assert_eq!(operations_str(None, "j").as_deref(), Some("cursor down 1"));
+        assert_eq!(
+            operations_str(Some(&visual), "j").as_deref(),
+            Some("select")
+        );
+        assert_eq!(operations_str(Some(&visual), "q").as_deref(), Some("exit"));
+    }
+
     #[test]
     fn test_parse_keybinding_cli() {
         let keybinding = "a:op multiple words+op2";
@@ -0,0 +1,167 @@
+use anyhow::{bail, Context, Error, Result};
+use derive_more::{From, IntoIterator};
+use parse_display::Display;
+use serde::Deserialize;
+use std::{collections::HashMap, str};
+
+use super::{KeybindingsParsed, KeybindingsToml, OperationParsed};
+
+/// Name of a user-defined mode, which has its own keybindings.
+#[derive(Debug, Clone, Display, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
+#[display("{0}")]
+pub struct ModeName(String);
+
+impl str::FromStr for ModeName {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.chars().any(char::is_whitespace) {
+            bail!(
+                "Failed to parse mode name '{}', it must be non-empty and must not contain whitespace.",
+                s
+            );
+        }
+        Ok(Self(s.to_owned()))
+    }
+}
+
+/// The keybindings of all user-defined modes.
+#[derive(Debug, Clone, PartialEq, Eq, Default, From, IntoIterator)]
+pub struct ModesParsed(#[into_iterator(owned, ref)] HashMap<ModeName, KeybindingsParsed>);
+
+impl ModesParsed {
+    /// Merge two sets of modes, where the keybindings of a mode are merged
+    /// with `opt_a`'s keybindings taking precedence over `opt_b`'s.
+    pub fn merge(opt_a: Option<Self>, opt_b: Option<Self>) -> Option<Self> {
+        match (opt_a, opt_b) {
+            (Some(a), Some(b)) => {
+                let mut merged = b.0;
+                for (name, a_keybindings) in a.0 {
+                    let b_keybindings = merged.remove(&name);
+                    let keybindings = KeybindingsParsed::merge(Some(a_keybindings), b_keybindings)
+                        .expect("merge should not be empty given `a` is some");
+                    merged.insert(name, keybindings);
+                }
+                Some(Self(merged))
+            }
+            (Some(a), None) => Some(a),
+            (None, opt_b) => opt_b,
+        }
+    }
+
+    /// Make sure that every `mode` operation, in the `default_keybindings`
+    /// and in the keybindings of all modes, refers to an existing mode.
+    pub fn check_mode_operations(&self, default_keybindings: &KeybindingsParsed) -> Result<()> {
+        let all_operations = std::iter::once(default_keybindings)
+            .chain(self.0.values())
+            .flat_map(KeybindingsParsed::operations);
+
+        for operation in all_operations {
+            if let OperationParsed::EnterMode(name) = operation {
+                if !self.0.contains_key(name) {
+                    bail!(
+                        "Operation \"{}\" refers to mode \"{}\", which is not defined",
+                        operation,
+                        name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// User-defined modes parsed from TOML.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ModesToml(HashMap<String, ModeToml>);
+
+/// A user-defined mode parsed from TOML.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
+pub struct ModeToml {
+    keybindings: Option<KeybindingsToml>,
+}
+
+impl TryFrom<ModesToml> for ModesParsed {
+    type Error = anyhow::Error;
+    fn try_from(value: ModesToml) -> Result<Self, Self::Error> {
+        let modes = value
+            .0
+            .into_iter()
+            .map(|(name, mode)| {
+                let keybindings = match mode.keybindings {
+                    Some(keybindings) => KeybindingsParsed::try_from(keybindings)
+                        .with_context(|| format!("Invalid keybindings in mode: {}", name))?,
+                    None => KeybindingsParsed::default(),
+                };
+                Ok((
+                    name.parse()
+                        .with_context(|| format!("Invalid mode name: {}", name))?,
+                    keybindings,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self(modes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{KeySequence, OperationsParsed};
+    use indoc::indoc;
+
+    fn parse_modes(modes: &str) -> Result<ModesParsed> {
+        #[derive(Deserialize)]
+        struct ModesTomlWrapper {
+            modes: ModesToml,
+        }
+        toml::from_str::<ModesTomlWrapper>(modes)?.modes.try_into()
+    }
+
+    #[test]
+    fn test_parse_toml_modes() {
+        let modes = parse_modes(indoc! {r#"
+            [modes.visual.keybindings]
+            "j" = [ "cursor down 1", "select" ]
+            "esc" = "mode-exit"
+
+            [modes.empty]
+        "#})
+        .unwrap();
+
+        let visual: ModeName = "visual".parse().unwrap();
+        let key: KeySequence = "j".parse().unwrap();
+        assert!(modes.0[&visual].0.contains_key(&key));
+        assert_eq!(
+            modes.0[&"empty".parse().unwrap()],
+            KeybindingsParsed::default()
+        );
+    }
+
+    #[test]
+    fn test_undefined_mode_is_rejected() {
+        let modes = parse_modes(indoc! {r#"
+            [modes.visual.keybindings]
+            "a" = "mode act"
+        "#})
+        .unwrap();
+        assert!(modes
+            .check_mode_operations(&KeybindingsParsed::default())
+            .is_err());
+
+        let default_keybindings: HashMap<KeySequence, OperationsParsed> = HashMap::from([(
+            "v".parse().unwrap(),
+            vec![OperationParsed::EnterMode("visual".parse().unwrap())].into(),
+        )]);
+        let modes = parse_modes(indoc! {r#"
+            [modes.visual.keybindings]
+            "esc" = "mode-exit"
+        "#})
+        .unwrap();
+        assert!(modes
+            .check_mode_operations(&default_keybindings.into())
+            .is_ok());
+    }
+}
@@ -4,11 +4,15 @@ use anyhow::{Context, Result};
 use derive_more::{From, IntoIterator};
 use itertools::Itertools;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+use crate::config::{OkExitCodes, Shell};
 use crate::ui::EnvVariables;
 
-pub use self::operation::{Operation, OperationExecutable, OperationParsed};
+pub use self::operation::{
+    IntervalChange, LinePattern, Operation, OperationExecutable, OperationParsed,
+};
 
 #[derive(IntoIterator, From)]
 pub struct Operations(#[into_iterator(ref)] Vec<Operation>);
@@ -17,12 +21,15 @@ impl Operations {
     pub fn from_parsed(
         operations_parsed: OperationsParsed,
         env_variables: &Arc<Mutex<EnvVariables>>,
+        shell: &Shell,
+        timeout: Option<Duration>,
+        ok_exit_codes: &OkExitCodes,
     ) -> Self {
         Self(
             operations_parsed
                 .0
                 .into_iter()
-                .map(|op| Operation::from_parsed(op, env_variables))
+                .map(|op| Operation::from_parsed(op, env_variables, shell, timeout, ok_exit_codes))
                 .collect(),
         )
     }
@@ -45,7 +52,6 @@ impl TryFrom<Vec<String>> for OperationsParsed {
                 // TODO: error messages from the parsing of attributes inside an operation (e.g. an EnvVariable) is not displayed here
                 op.parse()
                     .with_context(|| format!("Failed to parse operation: {}", op))
-                    .map_err(anyhow::Error::from)
             })
             .collect::<Result<_>>()?;
         Ok(Self(operations))
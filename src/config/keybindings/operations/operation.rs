@@ -1,13 +1,18 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Error, Result};
 use parse_display::{Display, FromStr};
-use std::str;
+use regex::Regex;
+use std::cmp::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::{fmt, str};
 use strum::{EnumIter, EnumMessage};
 use tokio::sync::mpsc::{self, Sender};
 use tokio::sync::Mutex;
 
-use crate::config::KeyEvent;
-use crate::ui::{EnvVariable, EnvVariables, Event, RequestedAction, State};
+use crate::config::{
+    Fields, KeySequence, ModeName, NonBlockingSubcommandLine, OkExitCodes, Shell, SubcommandLine,
+};
+use crate::ui::{EnvVariable, EnvVariables, Event, RequestedAction, State, WatchChange};
 use crate::utils::command::{
     Blocking, CommandBuilder, InheritedIO, NonBlocking, NonInterruptible, WithEnv, WithOutput,
 };
@@ -47,6 +52,18 @@ pub enum OperationParsed {
     Exit,
     Reload,
 
+    Pause,
+    Resume,
+    TogglePause,
+
+    #[display("interval {0}")]
+    #[strum(message = "interval [+|-]<SECS>")]
+    Interval(IntervalChange),
+
+    HistoryPrev,
+    HistoryNext,
+    HistoryLive,
+
     #[display("cursor up {0}")]
     #[strum(message = "cursor up <N>")]
     MoveCursorUp(usize),
@@ -61,6 +78,30 @@ pub enum OperationParsed {
     #[display("cursor last")]
     MoveCursorLast,
 
+    #[display("cursor mouse")]
+    MoveCursorToMouse,
+
+    #[display("cursor page-down")]
+    MoveCursorPageDown,
+
+    #[display("cursor page-up")]
+    MoveCursorPageUp,
+
+    #[display("cursor half-page-down")]
+    MoveCursorHalfPageDown,
+
+    #[display("cursor half-page-up")]
+    MoveCursorHalfPageUp,
+
+    #[display("cursor screen-top")]
+    MoveCursorScreenTop,
+
+    #[display("cursor screen-middle")]
+    MoveCursorScreenMiddle,
+
+    #[display("cursor screen-bottom")]
+    MoveCursorScreenBottom,
+
     #[display("select")]
     SelectLine,
 
@@ -76,21 +117,47 @@ pub enum OperationParsed {
     #[display("unselect-all")]
     UnselectAllLines,
 
-    #[display("exec -- {0}")]
-    #[strum(message = "exec -- <CMD>")]
-    ExecuteBlocking(String),
+    #[display("select-matching {0}")]
+    #[strum(message = "select-matching [--field <N>] <REGEX>")]
+    SelectMatchingLines(LinePattern),
+
+    #[display("unselect-matching {0}")]
+    #[strum(message = "unselect-matching [--field <N>] <REGEX>")]
+    UnselectMatchingLines(LinePattern),
+
+    #[display("invert-selection")]
+    InvertLineSelection,
+
+    #[display("select-start-range")]
+    StartRangeSelection,
 
-    #[display("exec & -- {0}")]
-    #[strum(message = "exec & -- <CMD>")]
-    ExecuteNonBlocking(String),
+    #[display("select-end-range")]
+    EndRangeSelection,
 
-    #[display("exec tui -- {0}")]
-    #[strum(message = "exec tui & -- <TUI-CMD>")]
-    ExecuteTUI(String),
+    #[display("toggle-range-selection")]
+    ToggleRangeSelection,
 
-    #[display("set-env {0} -- {1}")]
-    #[strum(message = "set-env <ENV> -- <CMD>")]
-    SetEnv(EnvVariable, String),
+    // The options of subcommands start with `--`, which distinguishes
+    // `exec` from the other `exec` operations while parsing.
+    #[display("exec {0}")]
+    #[strum(message = "exec [--argv] [--ok-exit-codes <CODES>] -- <CMD>")]
+    ExecuteBlocking(#[from_str(regex = "--(?s:.*)")] SubcommandLine),
+
+    #[display("exec & {0}")]
+    #[strum(message = "exec & [--argv] -- <CMD>")]
+    ExecuteNonBlocking(NonBlockingSubcommandLine),
+
+    #[display("exec tui {0}")]
+    #[strum(message = "exec tui & [--argv] [--ok-exit-codes <CODES>] -- <TUI-CMD>")]
+    ExecuteTUI(SubcommandLine),
+
+    #[display("exec popup {0}")]
+    #[strum(message = "exec popup [--argv] [--ok-exit-codes <CODES>] -- <CMD>")]
+    ExecutePopup(SubcommandLine),
+
+    #[display("set-env {0} {1}")]
+    #[strum(message = "set-env <ENV> [--argv] [--ok-exit-codes <CODES>] -- <CMD>")]
+    SetEnv(EnvVariable, #[from_str(regex = "--(?s:.*)")] SubcommandLine),
 
     #[display("unset-env {0}")]
     #[strum(message = "unset-env <ENV>")]
@@ -100,6 +167,23 @@ pub enum OperationParsed {
     #[strum(message = "read-into-env <ENV>")]
     ReadIntoEnv(EnvVariable),
 
+    Search,
+    SearchNext,
+    SearchPrev,
+
+    Filter,
+
+    #[display("confirm {0}")]
+    #[strum(message = "confirm \"<MESSAGE>\"")]
+    Confirm(ConfirmMessage),
+
+    #[display("mode {0}")]
+    #[strum(message = "mode <NAME>")]
+    EnterMode(ModeName),
+
+    #[display("mode-exit")]
+    ExitMode,
+
     HelpShow,
     HelpHide,
     HelpToggle,
@@ -108,6 +192,13 @@ pub enum OperationParsed {
 pub enum OperationExecutable {
     Exit,
     Reload,
+    Pause,
+    Resume,
+    TogglePause,
+    Interval(IntervalChange),
+    HistoryPrev,
+    HistoryNext,
+    HistoryLive,
     HelpShow,
     HelpHide,
     HelpToggle,
@@ -117,12 +208,20 @@ pub enum OperationExecutable {
     ExecuteBlocking(Arc<CommandBuilder<Blocking, WithEnv>>),
     ExecuteNonBlocking(Arc<CommandBuilder<NonBlocking, WithEnv>>),
     ExecuteTUI(Arc<CommandBuilder<Blocking, WithEnv, InheritedIO, NonInterruptible>>),
+    ExecutePopup(Arc<CommandBuilder<Blocking, WithEnv, WithOutput>>),
     SetEnv(
         EnvVariable,
         Arc<CommandBuilder<Blocking, WithEnv, WithOutput>>,
     ),
     UnsetEnv(EnvVariable),
     ReadIntoEnv(EnvVariable),
+    Search,
+    SearchNext,
+    SearchPrev,
+    Filter,
+    Confirm(ConfirmMessage),
+    EnterMode(ModeName),
+    ExitMode,
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Eq, Ord)]
@@ -131,6 +230,186 @@ pub enum MoveCursor {
     Up(usize),
     First,
     Last,
+    Mouse,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    ScreenTop,
+    ScreenMiddle,
+    ScreenBottom,
+}
+
+/// The message of a `confirm` operation, which may be surrounded by double
+/// quotes.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Default)]
+pub struct ConfirmMessage(String);
+
+impl str::FromStr for ConfirmMessage {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let message = s
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(s);
+        Ok(Self(message.to_owned()))
+    }
+}
+
+impl fmt::Display for ConfirmMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
+
+/// A change of the interval between executions of the watched command.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum IntervalChange {
+    /// Replace the interval, e.g. `interval 2`.
+    Set(Duration),
+    /// Lengthen the interval, e.g. `interval +0.5`.
+    Increase(Duration),
+    /// Shorten the interval, e.g. `interval -0.5`.
+    Decrease(Duration),
+}
+
+impl IntervalChange {
+    /// The smallest interval that can be reached by decreasing the interval,
+    /// because an interval of zero would disable periodic execution.
+    const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Apply the change to the current `interval`.
+    pub fn apply(&self, interval: Duration) -> Duration {
+        match self {
+            Self::Set(new_interval) => *new_interval,
+            Self::Increase(secs) => interval.saturating_add(*secs),
+            Self::Decrease(secs) => interval.saturating_sub(*secs).max(Self::MIN_INTERVAL),
+        }
+    }
+}
+
+impl Default for IntervalChange {
+    fn default() -> Self {
+        Self::Set(Duration::ZERO)
+    }
+}
+
+impl str::FromStr for IntervalChange {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (constructor, secs): (fn(Duration) -> Self, &str) =
+            if let Some(secs) = s.strip_prefix('+') {
+                (Self::Increase, secs)
+            } else if let Some(secs) = s.strip_prefix('-') {
+                (Self::Decrease, secs)
+            } else {
+                (Self::Set, s)
+            };
+        let secs: f64 = secs
+            .parse()
+            .with_context(|| format!("Invalid interval in seconds: {}", s))?;
+        if !secs.is_finite() || secs.is_sign_negative() {
+            bail!("Invalid interval in seconds: {}", s);
+        }
+        Ok(constructor(Duration::from_secs_f64(secs)))
+    }
+}
+
+impl fmt::Display for IntervalChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Set(secs) => write!(f, "{}", secs.as_secs_f64()),
+            Self::Increase(secs) => write!(f, "+{}", secs.as_secs_f64()),
+            Self::Decrease(secs) => write!(f, "-{}", secs.as_secs_f64()),
+        }
+    }
+}
+
+/// A regex that the unformatted content of a line, or only one of its fields,
+/// is matched against.
+#[derive(Debug, Clone)]
+pub struct LinePattern {
+    /// The 1-based number of the field to match, or `None` to match the
+    /// whole line.
+    field: Option<usize>,
+    regex: Regex,
+}
+
+impl LinePattern {
+    /// Returns whether the `line`, whose fields are separated according to
+    /// `fields`, matches the pattern.
+    pub fn is_match(&self, line: &str, fields: &Fields) -> bool {
+        let haystack = match self.field {
+            Some(number) => fields.get_field(line, number),
+            None => Some(line),
+        };
+        haystack.is_some_and(|haystack| self.regex.is_match(haystack))
+    }
+
+    fn sort_key(&self) -> (Option<usize>, &str) {
+        (self.field, self.regex.as_str())
+    }
+}
+
+impl PartialEq for LinePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.sort_key() == other.sort_key()
+    }
+}
+
+impl Eq for LinePattern {}
+
+impl PartialOrd for LinePattern {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LinePattern {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl Default for LinePattern {
+    fn default() -> Self {
+        Self {
+            field: None,
+            regex: Regex::new("").expect("empty regex should be valid"),
+        }
+    }
+}
+
+impl str::FromStr for LinePattern {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, regex) = match s.strip_prefix("--field ") {
+            Some(rest) => {
+                let (field, regex) = rest
+                    .split_once(' ')
+                    .with_context(|| format!("Missing regex after field in: {}", s))?;
+                let field: usize = field
+                    .parse()
+                    .with_context(|| format!("Invalid field number: {}", field))?;
+                if field == 0 {
+                    bail!("Invalid field number 0, fields start counting at 1");
+                }
+                (Some(field), regex)
+            }
+            None => (None, s),
+        };
+        let regex = Regex::new(regex).with_context(|| format!("Invalid regex: {}", regex))?;
+        Ok(Self { field, regex })
+    }
+}
+
+impl fmt::Display for LinePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(field) = self.field {
+            write!(f, "--field {} ", field)?;
+        }
+        write!(f, "{}", self.regex)
+    }
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Eq, Ord)]
@@ -140,24 +419,44 @@ pub enum SelectOperation {
     ToggleSelection,
     SelectAll,
     UnselectAll,
+    SelectMatching(LinePattern),
+    UnselectMatching(LinePattern),
+    Invert,
+    StartRange,
+    EndRange,
+    ToggleRange,
 }
 
 impl Operation {
     /// Execute the operation given the current `State` of the program. Perform
     /// any additional async communication with the main event loop through the
-    /// `event_tx` channel. Also use the `key_event` that triggered this
+    /// `event_tx` channel. Also use the `key_sequence` that triggered this
     /// operation for printing helpful error messages.
     pub async fn execute(
         &self,
         state: &mut State,
         event_tx: &Sender<Event>,
-        key_event: &KeyEvent,
+        key_sequence: &KeySequence,
     ) -> Result<RequestedAction> {
         match &self.executable {
             OperationExecutable::MoveCursor(MoveCursor::Down(steps)) => state.move_down(*steps),
             OperationExecutable::MoveCursor(MoveCursor::Up(steps)) => state.move_up(*steps),
             OperationExecutable::MoveCursor(MoveCursor::First) => state.move_to_first(),
             OperationExecutable::MoveCursor(MoveCursor::Last) => state.move_to_last(),
+            OperationExecutable::MoveCursor(MoveCursor::Mouse) => state.move_to_mouse(),
+            OperationExecutable::MoveCursor(MoveCursor::PageDown) => state.move_page_down(),
+            OperationExecutable::MoveCursor(MoveCursor::PageUp) => state.move_page_up(),
+            OperationExecutable::MoveCursor(MoveCursor::HalfPageDown) => {
+                state.move_half_page_down()
+            }
+            OperationExecutable::MoveCursor(MoveCursor::HalfPageUp) => state.move_half_page_up(),
+            OperationExecutable::MoveCursor(MoveCursor::ScreenTop) => state.move_to_screen_top(),
+            OperationExecutable::MoveCursor(MoveCursor::ScreenMiddle) => {
+                state.move_to_screen_middle()
+            }
+            OperationExecutable::MoveCursor(MoveCursor::ScreenBottom) => {
+                state.move_to_screen_bottom()
+            }
             OperationExecutable::SelectLine(SelectOperation::Select) => state.select(),
             OperationExecutable::SelectLine(SelectOperation::Unselect) => state.unselect(),
             OperationExecutable::SelectLine(SelectOperation::ToggleSelection) => {
@@ -165,10 +464,43 @@ impl Operation {
             }
             OperationExecutable::SelectLine(SelectOperation::SelectAll) => state.select_all(),
             OperationExecutable::SelectLine(SelectOperation::UnselectAll) => state.unselect_all(),
+            OperationExecutable::SelectLine(SelectOperation::SelectMatching(pattern)) => {
+                state.select_matching(pattern)
+            }
+            OperationExecutable::SelectLine(SelectOperation::UnselectMatching(pattern)) => {
+                state.unselect_matching(pattern)
+            }
+            OperationExecutable::SelectLine(SelectOperation::Invert) => state.invert_selection(),
+            OperationExecutable::SelectLine(SelectOperation::StartRange) => {
+                state.start_range_selection()
+            }
+            OperationExecutable::SelectLine(SelectOperation::EndRange) => {
+                state.end_range_selection()
+            }
+            OperationExecutable::SelectLine(SelectOperation::ToggleRange) => {
+                state.toggle_range_selection()
+            }
+            OperationExecutable::HistoryPrev => state.history_prev()?,
+            OperationExecutable::HistoryNext => state.history_next()?,
+            OperationExecutable::HistoryLive => state.history_live()?,
             OperationExecutable::HelpShow => state.show_help_menu().await,
             OperationExecutable::HelpHide => state.hide_help_menu(),
             OperationExecutable::HelpToggle => state.toggle_help_menu().await,
             OperationExecutable::Reload => return Ok(RequestedAction::ReloadWatchedCommand),
+            OperationExecutable::Pause => {
+                return Ok(RequestedAction::ChangeWatching(WatchChange::Pause))
+            }
+            OperationExecutable::Resume => {
+                return Ok(RequestedAction::ChangeWatching(WatchChange::Resume))
+            }
+            OperationExecutable::TogglePause => {
+                return Ok(RequestedAction::ChangeWatching(WatchChange::TogglePause))
+            }
+            OperationExecutable::Interval(change) => {
+                return Ok(RequestedAction::ChangeWatching(WatchChange::Interval(
+                    *change,
+                )))
+            }
             OperationExecutable::Exit => return Ok(RequestedAction::Exit),
             OperationExecutable::ExecuteNonBlocking(non_blocking_cmd) => {
                 state.add_cursor_and_selected_lines_to_env().await;
@@ -178,14 +510,22 @@ impl Operation {
             OperationExecutable::ExecuteBlocking(blocking_cmd) => {
                 state.add_cursor_and_selected_lines_to_env().await;
 
+                // Create channels for interrupting the subcommand.
+                let (interrupt_tx, mut interrupt_rx) = mpsc::channel(1);
+
                 let blocking_cmd = Arc::clone(blocking_cmd);
                 let event_tx = event_tx.clone();
                 // TODO: inefficient: creating Strings that are only used in the (rare) error-case
-                let (op_to_string, key_to_string) = (self.to_string(), key_event.to_string());
+                let (op_to_string, key_to_string) = (self.to_string(), key_sequence.to_string());
                 tokio::spawn(async move {
-                    let result = blocking_cmd.execute().await.with_context(|| {
-                        format!("Execution of blocking subcommand \"{}\", triggered by key event \"{}\", failed", op_to_string, key_to_string)
-                    });
+                    // An interrupted subcommand completes without error.
+                    let result = blocking_cmd
+                        .execute_interruptible(&mut interrupt_rx)
+                        .await
+                        .map(|_| ())
+                        .with_context(|| {
+                            format!("Execution of blocking subcommand \"{}\", triggered by key sequence \"{}\", failed", op_to_string, key_to_string)
+                        });
 
                     // Ignore whether the sender has closed channel.
                     let _ = event_tx.send(Event::SubcommandCompleted(result)).await;
@@ -195,7 +535,7 @@ impl Operation {
                 // here, because it would race with the spawned Tokio task. It
                 // will be called once this subcommand completes.
 
-                return Ok(RequestedAction::ExecutingBlockingSubcommand);
+                return Ok(RequestedAction::ExecutingBlockingSubcommand(interrupt_tx));
             }
             OperationExecutable::ExecuteTUI(tui_cmd) => {
                 state.add_cursor_and_selected_lines_to_env().await;
@@ -206,13 +546,13 @@ impl Operation {
                 let tui_cmd = Arc::clone(tui_cmd);
                 let event_tx = event_tx.clone();
                 // TODO: inefficient: creating Strings that are only used in the (rare) error-case
-                let (op_to_string, key_to_string) = (self.to_string(), key_event.to_string());
+                let (op_to_string, key_to_string) = (self.to_string(), key_sequence.to_string());
                 tokio::spawn(async move {
                     // Wait until TUI has actually been hidden.
                     let _ = tui_hidden_rx.recv().await;
 
                     let result = tui_cmd.execute().await.with_context(|| {
-                        format!("Execution of TUI subcommand \"{}\", triggered by key event \"{}\", failed", op_to_string, key_to_string)
+                        format!("Execution of TUI subcommand \"{}\", triggered by key sequence \"{}\", failed", op_to_string, key_to_string)
                     });
 
                     // Ignore whether the sender has closed channel.
@@ -225,18 +565,54 @@ impl Operation {
 
                 return Ok(RequestedAction::ExecutingTUISubcommand(tui_hidden_tx));
             }
+            OperationExecutable::ExecutePopup(blocking_cmd) => {
+                state.add_cursor_and_selected_lines_to_env().await;
+
+                // Create channels for interrupting the subcommand.
+                let (interrupt_tx, mut interrupt_rx) = mpsc::channel(1);
+
+                let blocking_cmd = Arc::clone(blocking_cmd);
+                let event_tx = event_tx.clone();
+                // TODO: inefficient: creating Strings that are only used in the (rare) error-case
+                let (op_to_string, key_to_string) = (self.to_string(), key_sequence.to_string());
+                tokio::spawn(async move {
+                    // An interrupted subcommand doesn't show any output.
+                    let result = blocking_cmd
+                        .execute_interruptible_with_stderr(&mut interrupt_rx)
+                        .await
+                        .with_context(|| {
+                            format!("Execution of popup subcommand \"{}\", triggered by key sequence \"{}\", failed", op_to_string, key_to_string)
+                        });
+
+                    // Ignore whether the sender has closed channel.
+                    let _ = event_tx.send(Event::PopupSubcommandCompleted(result)).await;
+                });
+
+                // Don't call state.remove_cursor_and_selected_lines_from_env()
+                // here, because it would race with the spawned Tokio task. It
+                // will be called once this subcommand completes.
+
+                return Ok(RequestedAction::ExecutingBlockingSubcommand(interrupt_tx));
+            }
             OperationExecutable::SetEnv(env_variable, blocking_cmd) => {
                 state.add_cursor_and_selected_lines_to_env().await;
 
+                // Create channels for interrupting the subcommand.
+                let (interrupt_tx, mut interrupt_rx) = mpsc::channel(1);
+
                 let blocking_cmd = blocking_cmd.clone();
                 let env_variable = env_variable.clone();
                 let event_tx = event_tx.clone();
                 tokio::spawn(async move {
-                    let result = blocking_cmd.execute().await.map(|output| {
-                        [(env_variable, output)]
-                            .into_iter()
-                            .collect::<EnvVariables>()
-                    });
+                    // An interrupted subcommand doesn't set any env variable.
+                    let result = blocking_cmd
+                        .execute_interruptible(&mut interrupt_rx)
+                        .await
+                        .map(|output| {
+                            output
+                                .map(|output| [(env_variable, output)].into_iter().collect())
+                                .unwrap_or_default()
+                        });
 
                     // Ignore whether the sender has closed channel.
                     let _ = event_tx
@@ -244,26 +620,98 @@ impl Operation {
                         .await;
                 });
 
-                return Ok(RequestedAction::ExecutingBlockingSubcommandForEnv);
+                return Ok(RequestedAction::ExecutingBlockingSubcommandForEnv(
+                    interrupt_tx,
+                ));
             }
             OperationExecutable::UnsetEnv(env) => state.unset_env(env).await,
-            OperationExecutable::ReadIntoEnv(env) => state.read_into_env(env).await,
+            OperationExecutable::ReadIntoEnv(env) => {
+                state.read_into_env(env);
+                return Ok(RequestedAction::ReadingTextInput);
+            }
+            OperationExecutable::Search => {
+                state.search();
+                return Ok(RequestedAction::ReadingTextInput);
+            }
+            OperationExecutable::SearchNext => state.search_next(),
+            OperationExecutable::SearchPrev => state.search_prev(),
+            OperationExecutable::Filter => {
+                state.filter();
+                return Ok(RequestedAction::ReadingTextInput);
+            }
+            OperationExecutable::Confirm(ConfirmMessage(message)) => {
+                state.confirm(message).await;
+                return Ok(RequestedAction::ReadingTextInput);
+            }
+            OperationExecutable::EnterMode(mode) => {
+                state.enter_mode(mode.clone());
+                return Ok(RequestedAction::ChangedMode);
+            }
+            OperationExecutable::ExitMode => {
+                state.exit_mode();
+                return Ok(RequestedAction::ChangedMode);
+            }
         };
         Ok(RequestedAction::Continue)
     }
 
     /// Convert the parsed form into the normal, runtime executable form. The
-    /// `env_variables` is required so it can be passed to the `SetEnv` command.
-    pub fn from_parsed(parsed: OperationParsed, env_variables: &Arc<Mutex<EnvVariables>>) -> Self {
+    /// `env_variables` is required so it can be passed to the `SetEnv` command,
+    /// and `shell` executes the commands of the `exec` and `SetEnv` commands.
+    /// Blocking commands that don't complete within `timeout` are killed,
+    /// except for TUI commands, which wait for user interaction. Blocking
+    /// commands are successful if they exit with any of the `ok_exit_codes`,
+    /// unless the operation sets its own. Non-blocking commands ignore the
+    /// `ok_exit_codes`, since their exit status is never checked.
+    pub fn from_parsed(
+        parsed: OperationParsed,
+        env_variables: &Arc<Mutex<EnvVariables>>,
+        shell: &Shell,
+        timeout: Option<Duration>,
+        ok_exit_codes: &OkExitCodes,
+    ) -> Self {
+        let ok_exit_codes_of =
+            |codes: Option<OkExitCodes>| codes.unwrap_or_else(|| ok_exit_codes.clone());
         let operation_executable = match parsed.clone() {
             OperationParsed::Exit => OperationExecutable::Exit,
             OperationParsed::Reload => OperationExecutable::Reload,
+            OperationParsed::Pause => OperationExecutable::Pause,
+            OperationParsed::Resume => OperationExecutable::Resume,
+            OperationParsed::TogglePause => OperationExecutable::TogglePause,
+            OperationParsed::Interval(change) => OperationExecutable::Interval(change),
+            OperationParsed::HistoryPrev => OperationExecutable::HistoryPrev,
+            OperationParsed::HistoryNext => OperationExecutable::HistoryNext,
+            OperationParsed::HistoryLive => OperationExecutable::HistoryLive,
             OperationParsed::MoveCursorUp(n) => OperationExecutable::MoveCursor(MoveCursor::Up(n)),
             OperationParsed::MoveCursorDown(n) => {
                 OperationExecutable::MoveCursor(MoveCursor::Down(n))
             }
             OperationParsed::MoveCursorFirst => OperationExecutable::MoveCursor(MoveCursor::First),
             OperationParsed::MoveCursorLast => OperationExecutable::MoveCursor(MoveCursor::Last),
+            OperationParsed::MoveCursorToMouse => {
+                OperationExecutable::MoveCursor(MoveCursor::Mouse)
+            }
+            OperationParsed::MoveCursorPageDown => {
+                OperationExecutable::MoveCursor(MoveCursor::PageDown)
+            }
+            OperationParsed::MoveCursorPageUp => {
+                OperationExecutable::MoveCursor(MoveCursor::PageUp)
+            }
+            OperationParsed::MoveCursorHalfPageDown => {
+                OperationExecutable::MoveCursor(MoveCursor::HalfPageDown)
+            }
+            OperationParsed::MoveCursorHalfPageUp => {
+                OperationExecutable::MoveCursor(MoveCursor::HalfPageUp)
+            }
+            OperationParsed::MoveCursorScreenTop => {
+                OperationExecutable::MoveCursor(MoveCursor::ScreenTop)
+            }
+            OperationParsed::MoveCursorScreenMiddle => {
+                OperationExecutable::MoveCursor(MoveCursor::ScreenMiddle)
+            }
+            OperationParsed::MoveCursorScreenBottom => {
+                OperationExecutable::MoveCursor(MoveCursor::ScreenBottom)
+            }
             OperationParsed::SelectLine => OperationExecutable::SelectLine(SelectOperation::Select),
             OperationParsed::UnselectLine => {
                 OperationExecutable::SelectLine(SelectOperation::Unselect)
@@ -277,33 +725,73 @@ impl Operation {
             OperationParsed::UnselectAllLines => {
                 OperationExecutable::SelectLine(SelectOperation::UnselectAll)
             }
+            OperationParsed::SelectMatchingLines(pattern) => {
+                OperationExecutable::SelectLine(SelectOperation::SelectMatching(pattern))
+            }
+            OperationParsed::UnselectMatchingLines(pattern) => {
+                OperationExecutable::SelectLine(SelectOperation::UnselectMatching(pattern))
+            }
+            OperationParsed::InvertLineSelection => {
+                OperationExecutable::SelectLine(SelectOperation::Invert)
+            }
+            OperationParsed::StartRangeSelection => {
+                OperationExecutable::SelectLine(SelectOperation::StartRange)
+            }
+            OperationParsed::EndRangeSelection => {
+                OperationExecutable::SelectLine(SelectOperation::EndRange)
+            }
+            OperationParsed::ToggleRangeSelection => {
+                OperationExecutable::SelectLine(SelectOperation::ToggleRange)
+            }
             OperationParsed::ExecuteBlocking(cmd) => {
                 OperationExecutable::ExecuteBlocking(Arc::new(
-                    CommandBuilder::new(cmd)
+                    CommandBuilder::new(cmd.command, shell.clone())
                         .blocking()
+                        .timeout(timeout)
+                        .ok_exit_codes(ok_exit_codes_of(cmd.ok_exit_codes))
                         .with_env(env_variables.clone()),
                 ))
             }
-            OperationParsed::ExecuteNonBlocking(cmd) => OperationExecutable::ExecuteNonBlocking(
-                Arc::new(CommandBuilder::new(cmd).with_env(env_variables.clone())),
-            ),
+            OperationParsed::ExecuteNonBlocking(NonBlockingSubcommandLine(command)) => {
+                OperationExecutable::ExecuteNonBlocking(Arc::new(
+                    CommandBuilder::new(command, shell.clone()).with_env(env_variables.clone()),
+                ))
+            }
             OperationParsed::ExecuteTUI(cmd) => OperationExecutable::ExecuteTUI(Arc::new(
-                CommandBuilder::new(cmd)
+                CommandBuilder::new(cmd.command, shell.clone())
                     .blocking()
+                    .ok_exit_codes(ok_exit_codes_of(cmd.ok_exit_codes))
                     .inherited_io()
                     .with_env(env_variables.clone()),
             )),
+            OperationParsed::ExecutePopup(cmd) => OperationExecutable::ExecutePopup(Arc::new(
+                CommandBuilder::new(cmd.command, shell.clone())
+                    .blocking()
+                    .timeout(timeout)
+                    .ok_exit_codes(ok_exit_codes_of(cmd.ok_exit_codes))
+                    .with_output()
+                    .with_env(env_variables.clone()),
+            )),
             OperationParsed::SetEnv(env_var, cmd) => OperationExecutable::SetEnv(
                 env_var,
                 Arc::new(
-                    CommandBuilder::new(cmd)
+                    CommandBuilder::new(cmd.command, shell.clone())
                         .blocking()
+                        .timeout(timeout)
+                        .ok_exit_codes(ok_exit_codes_of(cmd.ok_exit_codes))
                         .with_output()
                         .with_env(env_variables.clone()),
                 ),
             ),
             OperationParsed::UnsetEnv(x) => OperationExecutable::UnsetEnv(x),
             OperationParsed::ReadIntoEnv(x) => OperationExecutable::ReadIntoEnv(x),
+            OperationParsed::Search => OperationExecutable::Search,
+            OperationParsed::SearchNext => OperationExecutable::SearchNext,
+            OperationParsed::SearchPrev => OperationExecutable::SearchPrev,
+            OperationParsed::Filter => OperationExecutable::Filter,
+            OperationParsed::Confirm(message) => OperationExecutable::Confirm(message),
+            OperationParsed::EnterMode(mode) => OperationExecutable::EnterMode(mode),
+            OperationParsed::ExitMode => OperationExecutable::ExitMode,
             OperationParsed::HelpShow => OperationExecutable::HelpShow,
             OperationParsed::HelpHide => OperationExecutable::HelpHide,
             OperationParsed::HelpToggle => OperationExecutable::HelpToggle,
@@ -331,6 +819,106 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_exec() {
+        for op in [
+            "exec -- ls -l",
+            "exec & -- ls -l",
+            "exec --ok-exit-codes 0,1 -- grep foo",
+            "exec tui -- vim",
+            "exec popup --argv -- [\"ls\", \"-l\"]",
+            "set-env file --argv -- [\"echo\", \"$line\"]",
+        ] {
+            assert_eq!(op.parse::<OperationParsed>().unwrap().to_string(), op);
+        }
+        assert!(matches!(
+            "exec & -- ls".parse(),
+            Ok(OperationParsed::ExecuteNonBlocking(_))
+        ));
+        assert!("exec ls".parse::<OperationParsed>().is_err());
+        assert!("exec & --ok-exit-codes 1 -- ls"
+            .parse::<OperationParsed>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_confirm() {
+        let op: OperationParsed = r#"confirm "Delete $lines?""#.parse().unwrap();
+        assert_eq!(
+            op,
+            OperationParsed::Confirm(ConfirmMessage("Delete $lines?".to_owned()))
+        );
+        assert_eq!(op.to_string(), r#"confirm "Delete $lines?""#);
+
+        let op: OperationParsed = "confirm Are you sure?".parse().unwrap();
+        assert_eq!(
+            op,
+            OperationParsed::Confirm(ConfirmMessage("Are you sure?".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(
+            "interval 2".parse::<OperationParsed>().unwrap(),
+            OperationParsed::Interval(IntervalChange::Set(Duration::from_secs(2)))
+        );
+        assert_eq!(
+            "interval +0.5".parse::<OperationParsed>().unwrap(),
+            OperationParsed::Interval(IntervalChange::Increase(Duration::from_millis(500)))
+        );
+        assert_eq!(
+            "interval -1".parse::<OperationParsed>().unwrap(),
+            OperationParsed::Interval(IntervalChange::Decrease(Duration::from_secs(1)))
+        );
+        assert_eq!(
+            "interval +0.5"
+                .parse::<OperationParsed>()
+                .unwrap()
+                .to_string(),
+            "interval +0.5"
+        );
+        assert_eq!(
+            "toggle-pause".parse::<OperationParsed>().unwrap(),
+            OperationParsed::TogglePause
+        );
+        assert!("interval".parse::<OperationParsed>().is_err());
+        assert!("interval +-1".parse::<OperationParsed>().is_err());
+        assert!("interval fast".parse::<OperationParsed>().is_err());
+    }
+
+    #[test]
+    fn test_apply_interval_change() {
+        let interval = Duration::from_secs(1);
+        assert_eq!(
+            IntervalChange::Increase(Duration::from_millis(500)).apply(interval),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            IntervalChange::Decrease(Duration::from_secs(5)).apply(interval),
+            IntervalChange::MIN_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_parse_select_matching() {
+        let op: OperationParsed = "select-matching --field 3 ^Crash.*".parse().unwrap();
+        assert_eq!(op.to_string(), "select-matching --field 3 ^Crash.*");
+        let OperationParsed::SelectMatchingLines(pattern) = op else {
+            panic!("expected select-matching operation");
+        };
+        let fields = Fields::try_new(None, None).unwrap();
+        assert!(pattern.is_match("pod-a 1/1 CrashLoopBackOff", &fields));
+        assert!(!pattern.is_match("pod-b 1/1 Running", &fields));
+        assert!(!pattern.is_match("CrashLoopBackOff", &fields));
+
+        assert!("unselect-matching a b".parse::<OperationParsed>().is_ok());
+        assert!("select-matching (".parse::<OperationParsed>().is_err());
+        assert!("select-matching --field 0 x"
+            .parse::<OperationParsed>()
+            .is_err());
+    }
+
     #[test]
     fn test_parse_move_cursor_invalid_step_size() {
         assert!("cursor down -42".parse::<OperationParsed>().is_err());
@@ -0,0 +1,87 @@
+use anyhow::{bail, Context, Error, Result};
+use serde::Deserialize;
+use std::str;
+
+use super::Fields;
+
+/// Determines which line in the new output of the watched command a line of
+/// the old output corresponds to, so that its selection or the cursor can be
+/// kept on it across reloads.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(try_from = "String")]
+pub enum LineIdentity {
+    /// A line is identified by its position in the output.
+    #[default]
+    Index,
+    /// A line is identified by its whole content.
+    Line,
+    /// A line is identified by the content of the field with the 1-based
+    /// number.
+    Field(usize),
+}
+
+impl LineIdentity {
+    /// Get the key that identifies the `line`, whose fields are separated
+    /// according to `fields`, or `None` if the line is identified by its
+    /// position or doesn't have the key field.
+    pub fn key<'a>(&self, line: &'a str, fields: &Fields) -> Option<&'a str> {
+        match self {
+            Self::Index => None,
+            Self::Line => Some(line),
+            Self::Field(number) => fields.get_field(line, *number),
+        }
+    }
+}
+
+impl str::FromStr for LineIdentity {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let identity = match s.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["index"] => Self::Index,
+            ["line"] => Self::Line,
+            ["field", number] => {
+                let number: usize = number
+                    .parse()
+                    .with_context(|| format!("Invalid field number: {}", number))?;
+                if number == 0 {
+                    bail!("Invalid field number 0, fields start counting at 1");
+                }
+                Self::Field(number)
+            }
+            _ => bail!(
+                "Failed to parse \"{}\" as line identity, expected format is index|line|field <N>",
+                s
+            ),
+        };
+        Ok(identity)
+    }
+}
+
+impl TryFrom<String> for LineIdentity {
+    type Error = Error;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_identity() {
+        assert_eq!(
+            "index".parse::<LineIdentity>().unwrap(),
+            LineIdentity::Index
+        );
+        assert_eq!("line".parse::<LineIdentity>().unwrap(), LineIdentity::Line);
+        assert_eq!(
+            "field 2".parse::<LineIdentity>().unwrap(),
+            LineIdentity::Field(2)
+        );
+        assert!("field 0".parse::<LineIdentity>().is_err());
+        assert!("field".parse::<LineIdentity>().is_err());
+        assert!("content".parse::<LineIdentity>().is_err());
+    }
+}
@@ -1,5 +1,7 @@
+mod command_line;
 mod fields;
 mod keybindings;
+mod line_identity;
 mod style;
 mod table;
 mod xdg;
@@ -21,38 +23,66 @@ use terminal_size::Width;
 #[cfg(test)]
 use derive_builder::Builder;
 
-use crate::config::keybindings::{KeyCode, KeyModifier};
 use crate::config::style::PrettyColor;
 use crate::utils::possible_enum_values::PossibleEnumValues;
 
-use self::keybindings::{KeybindingCli, KeybindingsHelpMenuFormat, KeybindingsToml};
+use self::keybindings::{KeybindingCli, KeybindingsHelpMenuFormat, KeybindingsToml, ModesToml};
 use self::style::{Boldness, Color, Style};
 use self::{
     fields::{FieldSelections, FieldSeparator},
     keybindings::KeybindingsCli,
 };
 
+pub use self::command_line::{
+    CommandLine, NonBlockingSubcommandLine, OkExitCodes, Shell, SubcommandLine,
+};
 pub use self::fields::{Fields, TableFormatter};
 pub use self::keybindings::{
-    KeyEvent, Keybindings, KeybindingsParsed, KeybindingsPrintable, OperationExecutable,
-    OperationParsed, Operations, OperationsParsed,
+    IntervalChange, KeyCode, KeyEvent, KeyModifier, KeySequence, KeySequenceMatch, Keybindings,
+    KeybindingsParsed, KeybindingsPrintable, LinePattern, ModalKeybindings, ModeName, ModesParsed,
+    OperationExecutable, OperationParsed, Operations, OperationsParsed,
 };
+pub use self::line_identity::LineIdentity;
 pub use self::style::Styles;
 pub use self::table::Table;
 
 // TODO: don't have public members
 
 pub struct Config {
-    pub log_file: Option<PathBuf>,
-    pub watched_command: String,
+    pub line_source: LineSource,
+    pub shell: Shell,
     pub watch_rate: Duration,
+    pub timeout: Option<Duration>,
+    pub ok_exit_codes: OkExitCodes,
     pub styles: Styles,
     pub keybindings_parsed: KeybindingsParsed,
+    pub modes_parsed: ModesParsed,
     pub keybindings_help_menu_format: KeybindingsHelpMenuFormat,
+    pub key_sequence_timeout: Duration,
+    pub cancel_key: KeyEvent,
     pub header_lines: usize,
     pub fields: Fields,
+    pub selection_identity: LineIdentity,
+    pub cursor_identity: LineIdentity,
+    pub highlight_changes: bool,
+    pub highlight_changes_duration: Option<Duration>,
+    pub history_size: usize,
+    pub stream: bool,
+    pub stream_max_lines: usize,
     pub initial_env_ops: OperationsParsed,
     pub update_ui_while_blocking: bool,
+    pub exit_on_subcommand_failure: bool,
+    pub exit_on_watched_command_failure: bool,
+    pub status_bar: bool,
+    pub mouse: bool,
+}
+
+/// Where the displayed lines come from.
+pub enum LineSource {
+    /// The output of the watched command.
+    Command(CommandLine),
+    /// The lines piped into watchbind's stdin.
+    Stdin,
 }
 
 const GLOBAL_CONFIG_FILE: &str = "config.toml";
@@ -137,27 +167,74 @@ impl TryFrom<PartialConfig> for Config {
             config.selected_bg,
             Boldness::Unspecified,
         );
+        let search_match_style = Style::new(
+            config.search_match_fg,
+            config.search_match_bg,
+            config.search_match_boldness,
+        );
+        let changed_style = Style::new(
+            config.changed_fg,
+            config.changed_bg,
+            config.changed_boldness,
+        );
         let styles = Styles::new(
             non_cursor_non_header_style,
             cursor_style,
             header_style,
             selected_style,
+            search_match_style,
+            changed_style,
         );
 
+        let keybindings_parsed = expect!(config, keybindings);
+        let modes_parsed = config.modes.unwrap_or_default();
+        modes_parsed.check_mode_operations(&keybindings_parsed)?;
+
         Ok(Self {
-            log_file: config.log_file,
             initial_env_ops: config.initial_env_vars.unwrap_or_default().try_into()?,
-            watched_command: match config.watched_command {
-                Some(command) => command,
-                None => bail!("A command must be provided via command line or config file"),
+            line_source: match (config.stdin.unwrap_or_default(), config.watched_command) {
+                (true, _) => LineSource::Stdin,
+                (false, Some(command)) => LineSource::Command(command),
+                (false, None) => bail!(
+                    "A command must be provided via command line or config file, or lines must be piped into watchbind with --stdin"
+                ),
             },
+            shell: expect!(config, shell),
             watch_rate: Duration::from_secs_f64(expect!(config, interval)),
+            timeout: config
+                .timeout
+                .map(Duration::try_from_secs_f64)
+                .transpose()
+                .context("Invalid timeout")?,
+            ok_exit_codes: expect!(config, ok_exit_codes),
             styles,
-            keybindings_parsed: expect!(config, keybindings),
+            keybindings_parsed,
+            modes_parsed,
             keybindings_help_menu_format: expect!(config, keybindings_help_menu_format),
+            key_sequence_timeout: Duration::try_from_secs_f64(expect!(
+                config,
+                key_sequence_timeout
+            ))
+            .context("Invalid key-sequence-timeout")?,
+            cancel_key: expect!(config, cancel_key),
             header_lines: expect!(config, header_lines),
             fields: Fields::try_new(config.field_separator, config.field_selections)?,
+            selection_identity: expect!(config, selection_identity),
+            cursor_identity: expect!(config, cursor_identity),
+            highlight_changes: expect!(config, highlight_changes),
+            highlight_changes_duration: config
+                .highlight_changes_duration
+                .map(Duration::try_from_secs_f64)
+                .transpose()
+                .context("Invalid highlight-changes-duration")?,
+            history_size: expect!(config, history_size),
+            stream: expect!(config, stream),
+            stream_max_lines: expect!(config, stream_max_lines),
             update_ui_while_blocking: expect!(config, update_ui_while_blocking),
+            exit_on_subcommand_failure: expect!(config, exit_on_subcommand_failure),
+            exit_on_watched_command_failure: expect!(config, exit_on_watched_command_failure),
+            status_bar: expect!(config, status_bar),
+            mouse: expect!(config, mouse),
         })
     }
 }
@@ -169,8 +246,12 @@ impl TryFrom<PartialConfig> for Config {
 pub struct PartialConfig {
     log_file: Option<PathBuf>,
     initial_env_vars: Option<Vec<String>>,
-    watched_command: Option<String>,
+    watched_command: Option<CommandLine>,
+    stdin: Option<bool>,
+    shell: Option<Shell>,
     interval: Option<f64>,
+    timeout: Option<f64>,
+    ok_exit_codes: Option<OkExitCodes>,
     cursor_fg: Color,
     cursor_bg: Color,
     cursor_boldness: Boldness,
@@ -182,11 +263,31 @@ pub struct PartialConfig {
     non_cursor_non_header_bg: Color,
     non_cursor_non_header_boldness: Boldness,
     selected_bg: Color,
+    search_match_fg: Color,
+    search_match_bg: Color,
+    search_match_boldness: Boldness,
+    changed_fg: Color,
+    changed_bg: Color,
+    changed_boldness: Boldness,
     field_selections: Option<FieldSelections>,
     field_separator: Option<FieldSeparator>,
+    selection_identity: Option<LineIdentity>,
+    cursor_identity: Option<LineIdentity>,
+    highlight_changes: Option<bool>,
+    highlight_changes_duration: Option<f64>,
+    history_size: Option<usize>,
+    stream: Option<bool>,
+    stream_max_lines: Option<usize>,
     update_ui_while_blocking: Option<bool>,
+    exit_on_subcommand_failure: Option<bool>,
+    exit_on_watched_command_failure: Option<bool>,
+    status_bar: Option<bool>,
+    mouse: Option<bool>,
     keybindings: Option<KeybindingsParsed>,
+    modes: Option<ModesParsed>,
     keybindings_help_menu_format: Option<KeybindingsHelpMenuFormat>,
+    key_sequence_timeout: Option<f64>,
+    cancel_key: Option<KeyEvent>,
 }
 
 impl PartialConfig {
@@ -215,7 +316,11 @@ impl PartialConfig {
             log_file: self.log_file.or(other.log_file),
             initial_env_vars: self.initial_env_vars.or(other.initial_env_vars),
             watched_command: self.watched_command.or(other.watched_command),
+            stdin: self.stdin.or(other.stdin),
+            shell: self.shell.or(other.shell),
             interval: self.interval.or(other.interval),
+            timeout: self.timeout.or(other.timeout),
+            ok_exit_codes: self.ok_exit_codes.or(other.ok_exit_codes),
             non_cursor_non_header_fg: self
                 .non_cursor_non_header_fg
                 .or(other.non_cursor_non_header_fg),
@@ -232,16 +337,42 @@ impl PartialConfig {
             header_bg: self.header_bg.or(other.header_bg),
             header_boldness: self.header_boldness.or(other.header_boldness),
             selected_bg: self.selected_bg.or(other.selected_bg),
+            search_match_fg: self.search_match_fg.or(other.search_match_fg),
+            search_match_bg: self.search_match_bg.or(other.search_match_bg),
+            search_match_boldness: self.search_match_boldness.or(other.search_match_boldness),
+            changed_fg: self.changed_fg.or(other.changed_fg),
+            changed_bg: self.changed_bg.or(other.changed_bg),
+            changed_boldness: self.changed_boldness.or(other.changed_boldness),
             header_lines: self.header_lines.or(other.header_lines),
             field_separator: self.field_separator.or(other.field_separator),
+            selection_identity: self.selection_identity.or(other.selection_identity),
+            cursor_identity: self.cursor_identity.or(other.cursor_identity),
+            highlight_changes: self.highlight_changes.or(other.highlight_changes),
+            highlight_changes_duration: self
+                .highlight_changes_duration
+                .or(other.highlight_changes_duration),
+            history_size: self.history_size.or(other.history_size),
+            stream: self.stream.or(other.stream),
+            stream_max_lines: self.stream_max_lines.or(other.stream_max_lines),
             field_selections: self.field_selections.or(other.field_selections),
             update_ui_while_blocking: self
                 .update_ui_while_blocking
                 .or(other.update_ui_while_blocking),
+            exit_on_subcommand_failure: self
+                .exit_on_subcommand_failure
+                .or(other.exit_on_subcommand_failure),
+            exit_on_watched_command_failure: self
+                .exit_on_watched_command_failure
+                .or(other.exit_on_watched_command_failure),
+            status_bar: self.status_bar.or(other.status_bar),
+            mouse: self.mouse.or(other.mouse),
             keybindings: KeybindingsParsed::merge(self.keybindings, other.keybindings),
+            modes: ModesParsed::merge(self.modes, other.modes),
             keybindings_help_menu_format: self
                 .keybindings_help_menu_format
                 .or(other.keybindings_help_menu_format),
+            key_sequence_timeout: self.key_sequence_timeout.or(other.key_sequence_timeout),
+            cancel_key: self.cancel_key.or(other.cancel_key),
         }
     }
 
@@ -278,8 +409,11 @@ pub struct TomlFileConfig {
     #[serde(rename = "initial-env")]
     initial_env_vars: Option<Vec<String>>,
 
-    watched_command: Option<String>,
+    watched_command: Option<CommandLine>,
+    shell: Option<Shell>,
     interval: Option<f64>,
+    timeout: Option<f64>,
+    ok_exit_codes: Option<OkExitCodes>,
 
     #[serde(default)]
     cursor_fg: Color,
@@ -306,15 +440,54 @@ pub struct TomlFileConfig {
     #[serde(default)]
     selected_bg: Color,
 
+    #[serde(default)]
+    search_match_fg: Color,
+    #[serde(default)]
+    search_match_bg: Color,
+    #[serde(default)]
+    search_match_boldness: Boldness,
+
+    #[serde(default)]
+    changed_fg: Color,
+    #[serde(default)]
+    changed_bg: Color,
+    #[serde(default)]
+    changed_boldness: Boldness,
+
     #[serde(rename = "fields")]
     field_selections: Option<FieldSelections>,
     field_separator: Option<FieldSeparator>,
 
+    selection_identity: Option<LineIdentity>,
+    cursor_identity: Option<LineIdentity>,
+
+    highlight_changes: Option<bool>,
+    highlight_changes_duration: Option<f64>,
+
+    history_size: Option<usize>,
+
+    stream: Option<bool>,
+    stream_max_lines: Option<usize>,
+
     update_ui_while_blocking: Option<bool>,
 
+    exit_on_subcommand_failure: Option<bool>,
+
+    exit_on_watched_command_failure: Option<bool>,
+
+    status_bar: Option<bool>,
+
+    mouse: Option<bool>,
+
     keybindings: Option<KeybindingsToml>,
 
+    modes: Option<ModesToml>,
+
     keybindings_help_menu_format: Option<KeybindingsHelpMenuFormat>,
+
+    key_sequence_timeout: Option<f64>,
+
+    cancel_key: Option<KeyEvent>,
 }
 
 impl TomlFileConfig {
@@ -349,7 +522,12 @@ impl TryFrom<TomlFileConfig> for PartialConfig {
             log_file: toml.log_file,
             initial_env_vars: toml.initial_env_vars,
             watched_command: toml.watched_command,
+            // Lines can only be piped into watchbind via the command line.
+            stdin: None,
+            shell: toml.shell,
             interval: toml.interval,
+            timeout: toml.timeout,
+            ok_exit_codes: toml.ok_exit_codes,
             non_cursor_non_header_fg: toml.non_cursor_non_header_fg,
             non_cursor_non_header_bg: toml.non_cursor_non_header_bg,
             non_cursor_non_header_boldness: toml.non_cursor_non_header_boldness,
@@ -360,15 +538,35 @@ impl TryFrom<TomlFileConfig> for PartialConfig {
             header_bg: toml.header_bg,
             header_boldness: toml.header_boldness,
             selected_bg: toml.selected_bg,
+            search_match_fg: toml.search_match_fg,
+            search_match_bg: toml.search_match_bg,
+            search_match_boldness: toml.search_match_boldness,
+            changed_fg: toml.changed_fg,
+            changed_bg: toml.changed_bg,
+            changed_boldness: toml.changed_boldness,
             header_lines: toml.header_lines,
             field_separator: toml.field_separator,
+            selection_identity: toml.selection_identity,
+            cursor_identity: toml.cursor_identity,
+            highlight_changes: toml.highlight_changes,
+            highlight_changes_duration: toml.highlight_changes_duration,
+            history_size: toml.history_size,
+            stream: toml.stream,
+            stream_max_lines: toml.stream_max_lines,
             field_selections: toml.field_selections,
             update_ui_while_blocking: toml.update_ui_while_blocking,
+            exit_on_subcommand_failure: toml.exit_on_subcommand_failure,
+            exit_on_watched_command_failure: toml.exit_on_watched_command_failure,
+            status_bar: toml.status_bar,
+            mouse: toml.mouse,
             keybindings: toml
                 .keybindings
                 .map(KeybindingsParsed::try_from)
                 .transpose()?,
+            modes: toml.modes.map(ModesParsed::try_from).transpose()?,
             keybindings_help_menu_format: toml.keybindings_help_menu_format,
+            key_sequence_timeout: toml.key_sequence_timeout,
+            cancel_key: toml.cancel_key,
         })
     }
 }
@@ -379,8 +577,21 @@ impl TryFrom<CliArgs> for PartialConfig {
         Ok(Self {
             log_file: cli.log_file,
             initial_env_vars: cli.initial_env_vars,
-            watched_command: cli.watched_command.map(|s| s.join(" ")),
+            watched_command: cli
+                .watched_command
+                .map(|argv| {
+                    if cli.argv {
+                        CommandLine::try_from(argv)
+                    } else {
+                        Ok(CommandLine::Shell(argv.join(" ")))
+                    }
+                })
+                .transpose()?,
+            stdin: cli.stdin.then_some(true),
+            shell: cli.shell,
             interval: cli.interval,
+            timeout: cli.timeout,
+            ok_exit_codes: cli.ok_exit_codes,
             non_cursor_non_header_fg: cli.non_cursor_non_header_fg,
             non_cursor_non_header_bg: cli.non_cursor_non_header_bg,
             non_cursor_non_header_boldness: cli.non_cursor_non_header_boldness,
@@ -391,16 +602,37 @@ impl TryFrom<CliArgs> for PartialConfig {
             header_bg: cli.header_bg,
             header_boldness: cli.header_boldness,
             selected_bg: cli.selected_bg,
+            search_match_fg: cli.search_match_fg,
+            search_match_bg: cli.search_match_bg,
+            search_match_boldness: cli.search_match_boldness,
+            changed_fg: cli.changed_fg,
+            changed_bg: cli.changed_bg,
+            changed_boldness: cli.changed_boldness,
             header_lines: cli.header_lines,
             field_separator: cli.field_separator,
+            selection_identity: cli.selection_identity,
+            cursor_identity: cli.cursor_identity,
+            highlight_changes: cli.highlight_changes,
+            highlight_changes_duration: cli.highlight_changes_duration,
+            history_size: cli.history_size,
+            stream: cli.stream,
+            stream_max_lines: cli.stream_max_lines,
             field_selections: cli.field_selections,
             update_ui_while_blocking: cli.update_ui_while_blocking,
+            exit_on_subcommand_failure: cli.exit_on_subcommand_failure,
+            exit_on_watched_command_failure: cli.exit_on_watched_command_failure,
+            status_bar: cli.status_bar,
+            mouse: cli.mouse,
             keybindings: cli
                 .keybindings
                 .map(KeybindingsCli::from)
                 .map(KeybindingsParsed::try_from)
                 .transpose()?,
+            // Modes can only be defined in a TOML config file.
+            modes: None,
             keybindings_help_menu_format: cli.keybindings_help_menu_format,
+            key_sequence_timeout: cli.key_sequence_timeout,
+            cancel_key: cli.cancel_key,
         })
     }
 }
@@ -411,6 +643,10 @@ impl Default for PartialConfig {
         let default_toml = indoc! {r#"
             "interval" = 3.0
 
+            "shell" = [ "sh", "-c" ]
+
+            "ok-exit-codes" = [ 0 ]
+
             "cursor-fg" = "unspecified"
             "cursor-bg" = "blue"
             "cursor-boldness" = "bold"
@@ -420,16 +656,46 @@ impl Default for PartialConfig {
             "header-boldness" = "non-bold"
             "header-lines" = 0
 
+            "selection-identity" = "index"
+            "cursor-identity" = "line"
+
+            "highlight-changes" = false
+
+            "history-size" = 100
+
+            "stream" = false
+            "stream-max-lines" = 10000
+
             "non-cursor-non-header-fg" = "unspecified"
             "non-cursor-non-header-bg" = "unspecified"
             "non-cursor-non-header-boldness" = "unspecified"
 
             "selected-bg" = "magenta"
 
+            "search-match-fg" = "black"
+            "search-match-bg" = "yellow"
+            "search-match-boldness" = "unspecified"
+
+            "changed-fg" = "black"
+            "changed-bg" = "green"
+            "changed-boldness" = "unspecified"
+
             "update-ui-while-blocking" = false
 
+            "exit-on-subcommand-failure" = false
+
+            "exit-on-watched-command-failure" = false
+
+            "status-bar" = true
+
+            "mouse" = true
+
             "keybindings-help-menu-format" = [ "key", "description", "operations" ]
 
+            "key-sequence-timeout" = 1.0
+
+            "cancel-key" = "ctrl+c"
+
             [keybindings]
             "ctrl+c" = { description = "Exit watchbind", operations = "exit" }
             "q" = { description = "Exit watchbind", operations = "exit" }
@@ -442,11 +708,33 @@ impl Default for PartialConfig {
             "k" = { description = "Move cursor up 1 line", operations = "cursor up 1" }
             "g" = { description = "Move cursor to the first line", operations = "cursor first" }
             "G" = { description = "Move cursor to the last line"  , operations = "cursor last" }
+            "pagedown" = { description = "Move cursor down 1 page", operations = "cursor page-down" }
+            "pageup" = { description = "Move cursor up 1 page", operations = "cursor page-up" }
+            "ctrl+d" = { description = "Move cursor down half a page", operations = "cursor half-page-down" }
+            "ctrl+u" = { description = "Move cursor up half a page", operations = "cursor half-page-up" }
+            "leftclick" = { description = "Move cursor to the clicked line", operations = "cursor mouse" }
+            "scrolldown" = { description = "Move cursor down 3 lines", operations = "cursor down 3" }
+            "scrollup" = { description = "Move cursor up 3 lines", operations = "cursor up 3" }
 
             # Selecting lines
             "space" = { description = "Toggle selection of line that cursor is currently on, and move cursor down 1 line", operations = [ "toggle-selection", "cursor down 1" ] }
             "v" = { description = "Select line that cursor is currently on", operations = "select" }
+            "V" = { description = "Start or end selecting all lines between the current line and the cursor", operations = "toggle-range-selection" }
             "esc" = { description = "Unselect all currently selected lines", operations = "unselect-all" }
+            "ctrl+leftclick" = { description = "Toggle selection of the clicked line", operations = [ "cursor mouse", "toggle-selection" ] }
+
+            # Searching
+            "/" = { description = "Search for a pattern in all lines", operations = "search" }
+            "n" = { description = "Move cursor to the next line matching the search pattern", operations = "search-next" }
+            "N" = { description = "Move cursor to the previous line matching the search pattern", operations = "search-prev" }
+
+            # Filtering
+            "f" = { description = "Only display lines matching a filter pattern", operations = "filter" }
+
+            # Output history
+            "[" = { description = "Display the previous output of the watched command", operations = "history-prev" }
+            "]" = { description = "Display the next output of the watched command", operations = "history-next" }
+            "L" = { description = "Display the latest output of the watched command live", operations = "history-live" }
 
             # Help menu
             "?" = { description = "Toggle the visibility of the help menu", operations = "help-toggle" }
@@ -475,6 +763,20 @@ pub struct CliArgs {
     #[arg(trailing_var_arg(true))]
     watched_command: Option<Vec<String>>,
 
+    /// Execute the watched command directly as a program with its arguments,
+    /// instead of with the shell, e.g. `--argv -- ls -l "my dir"`
+    #[arg(long, requires = "watched_command")]
+    argv: bool,
+
+    /// Read the lines from stdin instead of executing a watched command, e.g.
+    /// `ls | watchbind --stdin`
+    #[arg(long, conflicts_with = "watched_command")]
+    stdin: bool,
+
+    /// Shell and its arguments that commands are executed with, e.g. `bash -euo pipefail -c`
+    #[arg(long, value_name = "SHELL")]
+    shell: Option<Shell>,
+
     /// File path to local TOML config file
     #[arg(short = 'c', long, value_name = "FILE")]
     local_config_file: Option<PathBuf>,
@@ -483,6 +785,16 @@ pub struct CliArgs {
     #[arg(short, long, value_name = "SECONDS")]
     interval: Option<f64>,
 
+    /// Seconds (f64) after which the watched command and blocking subcommands
+    /// are killed, if they haven't completed yet
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<f64>,
+
+    /// Comma-separated exit codes with which the watched command and blocking
+    /// subcommands are treated as successful, e.g. `0,1` for `grep`
+    #[arg(long, value_name = "CODES")]
+    ok_exit_codes: Option<OkExitCodes>,
+
     /// Foreground color of cursor line
     #[arg(
         long,
@@ -583,6 +895,66 @@ pub struct CliArgs {
     )]
     selected_bg: Color,
 
+    /// Foreground color of substrings matching the search pattern
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value_t,
+        hide_default_value = true,
+        hide_possible_values = true
+    )]
+    search_match_fg: Color,
+
+    /// Background color of substrings matching the search pattern
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value_t,
+        hide_default_value = true,
+        hide_possible_values = true
+    )]
+    search_match_bg: Color,
+
+    /// Boldness of substrings matching the search pattern
+    #[arg(
+        long,
+        value_name = "BOLDNESS",
+        default_value_t,
+        hide_default_value = true,
+        hide_possible_values = true
+    )]
+    search_match_boldness: Boldness,
+
+    /// Foreground color of lines that changed since the previous refresh
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value_t,
+        hide_default_value = true,
+        hide_possible_values = true
+    )]
+    changed_fg: Color,
+
+    /// Background color of lines that changed since the previous refresh
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value_t,
+        hide_default_value = true,
+        hide_possible_values = true
+    )]
+    changed_bg: Color,
+
+    /// Boldness of lines that changed since the previous refresh
+    #[arg(
+        long,
+        value_name = "BOLDNESS",
+        default_value_t,
+        hide_default_value = true,
+        hide_possible_values = true
+    )]
+    changed_boldness: Boldness,
+
     /// The first N lines of the input are treated as a sticky header
     #[arg(long, value_name = "N")]
     header_lines: Option<usize>,
@@ -595,18 +967,83 @@ pub struct CliArgs {
     #[arg(short = 'f', long = "fields", value_name = "LIST")]
     field_selections: Option<FieldSelections>,
 
+    /// How selected lines are identified in new output, to keep them
+    /// selected and to find changed lines: `index`, `line` or `field <N>`
+    /// (field indexes start at 1).
+    #[arg(long, value_name = "IDENTITY")]
+    selection_identity: Option<LineIdentity>,
+
+    /// How the line the cursor is on is identified in new output, to keep
+    /// the cursor on it: `index`, `line` or `field <N>` (field indexes start
+    /// at 1).
+    #[arg(long, value_name = "IDENTITY")]
+    cursor_identity: Option<LineIdentity>,
+
+    /// Whether to highlight lines that changed since the previous refresh,
+    /// similar to `watch -d`.
+    #[arg(long, value_name = "BOOL")]
+    highlight_changes: Option<bool>,
+
+    /// Seconds (f64) to highlight changed lines for, instead of until the
+    /// next refresh
+    #[arg(long, value_name = "SECONDS")]
+    highlight_changes_duration: Option<f64>,
+
+    /// Number of past outputs of the watched command to keep for navigating
+    /// the output history
+    #[arg(long, value_name = "N")]
+    history_size: Option<usize>,
+
+    /// Whether to execute the watched command only once and display its
+    /// output line by line as it arrives, e.g. for `tail -f`, instead of
+    /// executing it periodically.
+    #[arg(long, value_name = "BOOL")]
+    stream: Option<bool>,
+
+    /// Maximum number of the most recent lines kept from the output of a
+    /// streamed watched command
+    #[arg(long, value_name = "N")]
+    stream_max_lines: Option<usize>,
+
     /// Whether to update the UI with new output from the watched command
     /// while in a blocking state.
     #[arg(long, value_name = "BOOL")]
     update_ui_while_blocking: Option<bool>,
 
-    /// Keybindings as comma-separated `KEY:OP[+OP]*` pairs, e.g. `q:select+exit,r:reload`.
+    /// Whether to exit when a subcommand fails, instead of displaying the
+    /// error.
+    #[arg(long, value_name = "BOOL")]
+    exit_on_subcommand_failure: Option<bool>,
+
+    /// Whether to exit when the watched command fails, instead of keeping
+    /// the previous output and displaying the error.
+    #[arg(long, value_name = "BOOL")]
+    exit_on_watched_command_failure: Option<bool>,
+
+    /// Whether to display a status bar below the lines.
+    #[arg(long, value_name = "BOOL")]
+    status_bar: Option<bool>,
+
+    /// Whether to capture mouse events, which allows binding mouse buttons.
+    /// Disable to use the terminal's native text selection.
+    #[arg(long, value_name = "BOOL")]
+    mouse: Option<bool>,
+
+    /// Keybindings as comma-separated `KEY:OP[+OP]*` pairs, e.g. `q:select+exit,g g:cursor first`.
     #[arg(short = 'b', long = "bind", value_name = "LIST", value_delimiter = ',')]
     keybindings: Option<Vec<KeybindingCli>>,
 
     /// Format of keybindings help menu as comma-separated list, e.g. `key,operations,description`.
     #[arg(long, value_name = "FORMAT")]
     keybindings_help_menu_format: Option<KeybindingsHelpMenuFormat>,
+
+    /// Seconds (f64) to wait for the next key of a multi-key sequence
+    #[arg(long, value_name = "SECONDS")]
+    key_sequence_timeout: Option<f64>,
+
+    /// Key that cancels a running blocking subcommand, e.g. `ctrl+c`
+    #[arg(long, value_name = "KEY")]
+    cancel_key: Option<KeyEvent>,
 }
 
 /// Convert [[&str, String]] to [[Cow::Borrowed(&str), Cow::Owned(&str)]].
@@ -647,7 +1084,11 @@ impl CliArgs {
         let possible_values_table_data = cowify![
             ["COLOR", format!("[{color}]")],
             ["BOLDNESS", format!("[{boldness}]")],
-            ["KEY", format!("[<KEY-MODIFIER>+<KEY-CODE>, <KEY-CODE>]")],
+            ["KEY", format!("[<KEY-EVENT>, <KEY-EVENT> <KEY-EVENT>...]")],
+            [
+                "KEY-EVENT",
+                format!("[<KEY-MODIFIER>+<KEY-CODE>, <KEY-CODE>]")
+            ],
             ["KEY-MODIFIER", format!("[{key_modifier}]")],
             ["KEY-CODE", format!("[{key_code}]")],
             ["OP", format!("[{operation}]")],
@@ -752,4 +1193,54 @@ mod tests {
         assert_a_overrides_b_on_attribute!(local, default, header_bg, merged);
         assert_a_overrides_b_on_attribute!(global, default, header_lines, merged);
     }
+
+    #[test]
+    fn test_invalid_durations() {
+        let config_with = |cli: PartialConfig| {
+            let cli = PartialConfig {
+                watched_command: Some(CommandLine::default()),
+                ..cli
+            };
+            Config::try_from(cli.merge(PartialConfig::default()))
+        };
+
+        assert!(config_with(PartialConfigBuilder::default().build().unwrap()).is_ok());
+        for invalid in [-1.0, f64::NAN] {
+            let cli = PartialConfigBuilder::default()
+                .key_sequence_timeout(Some(invalid))
+                .build()
+                .unwrap();
+            assert!(config_with(cli).is_err());
+
+            let cli = PartialConfigBuilder::default()
+                .timeout(Some(invalid))
+                .build()
+                .unwrap();
+            assert!(config_with(cli).is_err());
+
+            let cli = PartialConfigBuilder::default()
+                .highlight_changes_duration(Some(invalid))
+                .build()
+                .unwrap();
+            assert!(config_with(cli).is_err());
+        }
+    }
+
+    #[test]
+    fn test_line_source() {
+        let config_with =
+            |cli: PartialConfig| Config::try_from(cli.merge(PartialConfig::default()));
+
+        let stdin = PartialConfig {
+            stdin: Some(true),
+            ..PartialConfigBuilder::default().build().unwrap()
+        };
+        assert!(matches!(
+            config_with(stdin).unwrap().line_source,
+            LineSource::Stdin
+        ));
+
+        let neither = PartialConfigBuilder::default().build().unwrap();
+        assert!(config_with(neither).is_err());
+    }
 }
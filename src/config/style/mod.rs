@@ -20,6 +20,11 @@ pub struct Styles {
     /// The style of the indicator in selected lines (not the style of the
     /// selected lines themselves).
     pub selected: RatatuiStyle,
+    /// The style of the substrings that match the current search pattern.
+    pub search_match: RatatuiStyle,
+    /// The style of the lines that changed since the previous refresh, if
+    /// the cursor is not on them.
+    pub changed: RatatuiStyle,
 }
 
 /// A style encompassing fg, bg and boldness.
@@ -40,12 +45,16 @@ impl Styles {
         cursor_style: Style,
         header_style: Style,
         selected_style: Style,
+        search_match_style: Style,
+        changed_style: Style,
     ) -> Self {
         Self {
             non_cursor_non_header: non_cursor_non_header_style.into(),
             cursor: cursor_style.into(),
             header: header_style.into(),
             selected: selected_style.into(),
+            search_match: search_match_style.into(),
+            changed: changed_style.into(),
         }
     }
 }
@@ -1,23 +1,32 @@
 mod state;
 mod terminal_manager;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use crossterm::event::{
     Event as CrosstermEvent, EventStream, KeyEvent as CrosstermKeyEvent, KeyEventKind,
 };
 use futures::{future::FutureExt, StreamExt};
+use itertools::Itertools;
+use std::collections::VecDeque;
+use std::io::{BufRead, IsTerminal};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use terminal_manager::Tui;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
-use crate::config::{Config, KeyEvent, Keybindings, KeybindingsPrintable};
+use crate::config::{
+    Config, IntervalChange, KeyEvent, KeySequence, KeySequenceMatch, Keybindings,
+    KeybindingsPrintable, LineSource, ModalKeybindings, ModeName,
+};
 use crate::utils::command::{
-    Blocking, CommandBuilder, ExecutionResult, Interruptible, WasWoken, WithEnv, WithOutput,
+    is_command_failure, Blocking, CommandBuilder, ExecutionResult, ExitError, Interruptible,
+    StreamingChild, SubcommandOutput, WasWoken, WithEnv, WithOutput,
 };
 
+use self::state::{Lines, StatusBar};
+
 pub use self::state::State;
-pub use self::state::{EnvVariable, EnvVariables};
+pub use self::state::{EnvVariable, EnvVariables, PromptState};
 
 pub type WatchedCommand = CommandBuilder<Blocking, WithEnv, WithOutput, Interruptible>;
 
@@ -26,19 +35,33 @@ pub struct UI {
     tui: Tui,
     state: State,
     watch_rate: Duration,
-    keybindings: Arc<Keybindings>,
+    /// Whether the periodic execution of the watched command is paused.
+    paused: bool,
+    keybindings: Arc<ModalKeybindings>,
     remaining_operations: Option<RemainingOperations>,
+    /// Interrupts the blocking subcommand that is currently executing, if
+    /// any.
+    subcommand_interrupt_tx: Option<Sender<InterruptSignal>>,
     channels: Channels,
     update_ui_while_blocking: bool,
+    exit_on_subcommand_failure: bool,
+    exit_on_watched_command_failure: bool,
+    key_sequence_timeout: Duration,
+    /// The key that cancels the blocking subcommand that is currently
+    /// executing.
+    cancel_key: KeyEvent,
 }
 
 /// After having blocked, there might be some remaining operations, that
 /// were originally requested, which we still have to execute.
 #[derive(Debug)]
 struct RemainingOperations {
-    /// The key that is mapped to the remaining operations. Saving this is
-    /// more (memory) efficient than copying the an partial Operations type.
-    key: KeyEvent,
+    /// The mode in which the key sequence was pressed.
+    mode: Option<ModeName>,
+    /// The key sequence that is mapped to the remaining operations. Saving
+    /// this is more (memory) efficient than copying the an partial Operations
+    /// type.
+    key: KeySequence,
     /// The index in the Operations vector where the remaining operations start.
     remaining_index: usize,
 }
@@ -51,6 +74,7 @@ struct Channels {
     // We don't store the receivers for these channels,
     // because their ownership is passed to the polling tasks.
     reload_tx: Sender<InterruptSignal>,
+    watch_tx: Sender<WatchCommand>,
     polling_tx: Sender<PollingCommand>,
 }
 
@@ -58,19 +82,40 @@ struct Channels {
 /// passed to polling tasks it would leave the UI in a partially moved state,
 /// preventing us from calling methods on it.
 struct PollingState {
-    /// The command of which the output is 'watched'.
-    watched_command: WatchedCommand,
+    /// Where the lines that are displayed come from.
+    line_source: PolledLineSource,
+    watch_rx: Receiver<WatchCommand>,
     polling_rx: Receiver<PollingCommand>,
 }
 
+/// The ways lines can be polled.
+enum PolledLineSource {
+    /// The output of the watched command, which is executed periodically.
+    Periodic(WatchedCommand),
+    /// The output of the watched command, which is executed once and
+    /// streamed, keeping at most the given number of lines.
+    Streamed(WatchedCommand, usize),
+    /// The lines piped into stdin, keeping at most the given number of lines.
+    Stdin(usize, Receiver<InterruptSignal>),
+}
+
 /// Events that are handled in our main UI/IO loop.
 pub enum Event {
     /// The output of a completed command.
     CommandOutput(Result<String>),
-    /// A key has been pressed.
+    /// The watched command has exited with the given exit code, or without
+    /// one, e.g. because it was killed.
+    WatchedCommandExited(Option<i32>),
+    /// The key sequence of a keybinding has been pressed.
+    KeybindingPressed(KeySequence),
+    /// A key has been pressed, which should not be interpreted as part of
+    /// a keybinding.
     KeyPressed(KeyEvent),
     /// The terminal has been resized.
     TerminalResized,
+    /// A mouse button has been pressed, or the mouse wheel scrolled, at the
+    /// given terminal row. Sent right before the corresponding key sequence.
+    MousePressed { row: u16 },
     /// A subcommand has finished executing.
     SubcommandCompleted(Result<()>),
     /// The output of a completed subcommand, that should now be set to an
@@ -78,6 +123,9 @@ pub enum Event {
     SubcommandForEnvCompleted(Result<EnvVariables>),
     /// A TUI subcommand has finished executing.
     TUISubcommandCompleted(Result<()>),
+    /// The stdout and stderr of a completed subcommand, that should now be
+    /// shown in a popup, or `None` if the subcommand was interrupted.
+    PopupSubcommandCompleted(Result<Option<SubcommandOutput>>),
 }
 
 // TODO: maybe move to operations module
@@ -88,18 +136,36 @@ pub enum RequestedAction {
     /// Reload/rerun the main command, while blocking.
     ReloadWatchedCommand,
     /// Signals that a blocking subcommand has started executing, so we
-    /// should block.
-    ExecutingBlockingSubcommand,
+    /// should block. The subcommand can be interrupted through the sender.
+    ExecutingBlockingSubcommand(Sender<InterruptSignal>),
     /// Signals that a blocking subcommand used to set env variables has
-    /// started executing, so we should block.
-    ExecutingBlockingSubcommandForEnv,
+    /// started executing, so we should block. The subcommand can be
+    /// interrupted through the sender.
+    ExecutingBlockingSubcommandForEnv(Sender<InterruptSignal>),
     /// Signals that watchbind's TUI needs to be hidden so the TUI subcommand
     /// can be displayed. Notifies event's sender once TUI is finally hidden.
     ExecutingTUISubcommand(Sender<()>),
+    /// Signals that the user is typing into a text input, so we should
+    /// block.
+    ReadingTextInput,
+    /// Signals that another mode has been activated, whose keybindings
+    /// should be used from now on.
+    ChangedMode,
+    /// Change how the watched command is periodically executed.
+    ChangeWatching(WatchChange),
     /// Exit the application.
     Exit,
 }
 
+/// A change to the periodic execution of the watched command, requested by
+/// an operation.
+pub enum WatchChange {
+    Pause,
+    Resume,
+    TogglePause,
+    Interval(IntervalChange),
+}
+
 // TODO: use rust type state pattern
 // TODO: split into Unblocked|Blocked and then reason why blocked
 
@@ -113,8 +179,15 @@ enum BlockingState {
     BlockedExecutingSubcommand,
     BlockedExecutingSubcommandForEnv,
     BlockedExecutingTUISubcommand,
+    BlockedReadingTextInput,
+    BlockedShowingError,
+    BlockedShowingOutput,
 }
 
+/// The maximum duration between two redraws of the UI while the status bar is
+/// displayed.
+const STATUS_BAR_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Clean wrapper around draw() which prevents borrow-checking problems caused
 /// by mutably borrowing self.
 macro_rules! draw {
@@ -132,9 +205,10 @@ fn draw(tui: &mut Tui, state: &mut State) -> Result<()> {
 /// Save all remaining operations, if there are any. Used as macro to prevent
 /// borrow-checking problems.
 macro_rules! save_remaining_operations {
-    ($self:expr, $key:expr, $remaining_index:expr, $operations:expr) => {
+    ($self:expr, $mode:expr, $key:expr, $remaining_index:expr, $operations:expr) => {
         if $remaining_index < $operations.len() {
             $self.remaining_operations = Some(RemainingOperations {
+                mode: $mode,
                 key: $key,
                 remaining_index: $remaining_index,
             });
@@ -157,22 +231,58 @@ impl UI {
     }
 
     async fn new(config: Config) -> Result<(Self, PollingState)> {
-        let terminal_manager = Tui::new()?;
+        // Check before taking over the terminal, so the error is printed
+        // to the user's terminal.
+        if matches!(config.line_source, LineSource::Stdin) && std::io::stdin().is_terminal() {
+            bail!("Reading lines from stdin requires lines to be piped into watchbind");
+        }
+
+        let terminal_manager = Tui::new(config.mouse)?;
+
+        // Lines piped into stdin are always streamed.
+        let streaming = config.stream || matches!(config.line_source, LineSource::Stdin);
 
         // Create `State`.
         // let keybindings_str = config.keybindings_parsed.to_string();
         let mut state = State::new(
-            config.header_lines,
-            config.fields,
-            config.styles,
+            Lines::new(
+                config.fields,
+                config.styles,
+                config.header_lines,
+                config.selection_identity,
+                config.cursor_identity,
+                config.highlight_changes,
+                config.highlight_changes_duration,
+            ),
+            config.history_size,
+            streaming,
             KeybindingsPrintable::new(
                 config.keybindings_parsed.clone(),
-                config.keybindings_help_menu_format,
+                config.keybindings_help_menu_format.clone(),
             ),
-            EnvVariables::new(),
+            config
+                .modes_parsed
+                .clone()
+                .into_iter()
+                .map(|(mode, keybindings_parsed)| {
+                    let keybindings_printable = KeybindingsPrintable::new(
+                        keybindings_parsed,
+                        config.keybindings_help_menu_format.clone(),
+                    );
+                    (mode, keybindings_printable)
+                })
+                .collect(),
+            config
+                .status_bar
+                .then(|| StatusBar::new(config.watch_rate, streaming)),
         );
         state
-            .generate_initial_env_vars(config.initial_env_ops)
+            .generate_initial_env_vars(
+                config.initial_env_ops,
+                &config.shell,
+                config.timeout,
+                &config.ok_exit_codes,
+            )
             .await?;
 
         // TODO: room for optimization: we can probably get away with much smaller buffer sizes for some of our channels
@@ -184,17 +294,41 @@ impl UI {
 
         let (event_tx, event_rx) = mpsc::channel(TOKIO_DEFAULT_CHANNEL_BUFFER_CAPACITY);
         let (reload_tx, reload_rx) = mpsc::channel(TOKIO_DEFAULT_CHANNEL_BUFFER_CAPACITY);
+        let (watch_tx, watch_rx) = mpsc::channel(TOKIO_DEFAULT_CHANNEL_BUFFER_CAPACITY);
         let (polling_tx, polling_rx) = mpsc::channel(TOKIO_DEFAULT_CHANNEL_BUFFER_CAPACITY);
 
         let env_variables = state.get_env();
-        let keybindings = Keybindings::from_parsed(config.keybindings_parsed, &env_variables);
+        let keybindings = ModalKeybindings::from_parsed(
+            config.keybindings_parsed,
+            config.modes_parsed,
+            &env_variables,
+            &config.shell,
+            config.timeout,
+            &config.ok_exit_codes,
+        );
+
+        let line_source = match config.line_source {
+            LineSource::Command(watched_command) => {
+                let watched_command = CommandBuilder::new(watched_command, config.shell)
+                    .blocking()
+                    .ok_exit_codes(config.ok_exit_codes)
+                    .with_output()
+                    .interruptible(reload_rx)
+                    .with_env(env_variables.clone());
+                if config.stream {
+                    PolledLineSource::Streamed(watched_command, config.stream_max_lines)
+                } else {
+                    // A streamed command may run indefinitely, so only
+                    // periodically executed commands can time out.
+                    PolledLineSource::Periodic(watched_command.timeout(config.timeout))
+                }
+            }
+            LineSource::Stdin => PolledLineSource::Stdin(config.stream_max_lines, reload_rx),
+        };
 
         let polling_state = PollingState {
-            watched_command: CommandBuilder::new(config.watched_command)
-                .blocking()
-                .with_output()
-                .interruptible(reload_rx)
-                .with_env(env_variables.clone()),
+            line_source,
+            watch_rx,
             polling_rx,
         };
 
@@ -203,15 +337,22 @@ impl UI {
             tui: terminal_manager,
             state,
             watch_rate: config.watch_rate,
+            paused: false,
             keybindings: Arc::new(keybindings),
             remaining_operations: None,
+            subcommand_interrupt_tx: None,
             channels: Channels {
                 event_tx,
                 event_rx,
                 reload_tx,
+                watch_tx,
                 polling_tx,
             },
             update_ui_while_blocking: config.update_ui_while_blocking,
+            exit_on_subcommand_failure: config.exit_on_subcommand_failure,
+            exit_on_watched_command_failure: config.exit_on_watched_command_failure,
+            key_sequence_timeout: config.key_sequence_timeout,
+            cancel_key: config.cancel_key,
         };
 
         Ok((ui, polling_state))
@@ -220,13 +361,33 @@ impl UI {
     /// Run the main event loop indefinitely until an Exit request is received.
     async fn run(mut self, polling_state: PollingState) -> Result<()> {
         // Launch polling tasks
-        tokio::spawn(poll_execute_watched_command(
-            polling_state.watched_command,
-            self.watch_rate,
-            self.channels.event_tx.clone(),
-        ));
+        match polling_state.line_source {
+            PolledLineSource::Periodic(watched_command) => {
+                tokio::spawn(poll_execute_watched_command(
+                    watched_command,
+                    self.watch_rate,
+                    self.channels.event_tx.clone(),
+                    polling_state.watch_rx,
+                ))
+            }
+            PolledLineSource::Streamed(watched_command, max_lines) => {
+                tokio::spawn(poll_stream_watched_command(
+                    watched_command,
+                    max_lines,
+                    self.channels.event_tx.clone(),
+                    polling_state.watch_rx,
+                ))
+            }
+            PolledLineSource::Stdin(max_lines, reload_rx) => tokio::spawn(poll_read_stdin(
+                max_lines,
+                self.channels.event_tx.clone(),
+                reload_rx,
+                polling_state.watch_rx,
+            )),
+        };
         tokio::spawn(poll_terminal_events(
             self.keybindings.clone(),
+            self.key_sequence_timeout,
             self.channels.event_tx.clone(),
             polling_state.polling_rx,
         ));
@@ -240,15 +401,44 @@ impl UI {
                 }
             };
 
-            let Some(event) = self.channels.event_rx.recv().await else {
+            // Redraw periodically, so that the time since the last refresh
+            // displayed in the status bar stays up to date, and so that
+            // highlights of changed lines disappear once they expire.
+            let redraw_timeout = self
+                .state
+                .has_status_bar()
+                .then_some(STATUS_BAR_REDRAW_INTERVAL)
+                .into_iter()
+                .chain(self.state.next_change_expiry())
+                .min();
+            let event = if let Some(redraw_timeout) = redraw_timeout {
+                match tokio::time::timeout(redraw_timeout, self.channels.event_rx.recv()).await {
+                    Ok(event) => event,
+                    Err(_) => continue 'event_loop,
+                }
+            } else {
+                self.channels.event_rx.recv().await
+            };
+            let Some(event) = event else {
                 // Event channel has been closed.
                 break 'event_loop;
             };
 
             // Handle events that are handled the same in every state.
-            if let Event::TerminalResized = &event {
-                // Reload the UI.
-                continue 'event_loop;
+            match &event {
+                Event::TerminalResized => {
+                    // Reload the UI.
+                    continue 'event_loop;
+                }
+                Event::MousePressed { row } => {
+                    self.state.set_mouse_row(*row);
+                    continue 'event_loop;
+                }
+                Event::WatchedCommandExited(exit_code) => {
+                    self.state.set_exit_code(*exit_code).await;
+                    continue 'event_loop;
+                }
+                _ => {}
             }
 
             // Note: all states also handle Event::CommandOutput very similarly,
@@ -259,24 +449,34 @@ impl UI {
             match self.blocking_state {
                 BlockingState::Unblocked => match event {
                     Event::CommandOutput(lines) => {
-                        self.state.update_lines(lines?)?;
+                        self.handle_command_output(lines)?;
                     }
-                    Event::KeyPressed(key) => {
-                        if let ControlFlow::Exit = self.handle_key_event(key).await? {
+                    Event::KeybindingPressed(key) => {
+                        // The terminal event listener may have matched the key
+                        // sequence against the keybindings of the previous
+                        // mode, if it was pressed right after changing modes
+                        // but before the listener was told about the change.
+                        // Therefore, we look up its operations in the mode
+                        // that is actually active now.
+                        let mode = self.state.active_mode().cloned();
+                        if let ControlFlow::Exit = self.handle_key_event(mode, key).await? {
                             break 'event_loop;
                         }
                     }
+                    // Currently not reading any text input.
+                    Event::KeyPressed(_) => {}
                     // Already handled before.
-                    Event::TerminalResized => {}
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
                     // Currently not blocking, so should never receive completed subcommand events.
                     Event::SubcommandCompleted(_)
                     | Event::SubcommandForEnvCompleted(_)
-                    | Event::TUISubcommandCompleted(_) => {}
+                    | Event::TUISubcommandCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
                 },
                 BlockingState::BlockedExecutingTUISubcommand => match event {
-                    Event::TUISubcommandCompleted(potential_error) => {
-                        potential_error?;
-
+                    Event::TUISubcommandCompleted(result) => {
                         // Remove temporary env vars that were added just for execution.
                         self.state.remove_cursor_and_selected_lines_from_env().await;
 
@@ -289,59 +489,189 @@ impl UI {
                             .send(PollingCommand::Listen)
                             .await?;
 
-                        if let ControlFlow::Exit = self.conclude_blocking().await? {
+                        let control_flow = match result {
+                            Ok(()) => self.conclude_blocking().await?,
+                            Err(error) => self.handle_subcommand_failure(error).await?,
+                        };
+                        if let ControlFlow::Exit = control_flow {
                             break 'event_loop;
                         }
                     }
                     // Our TUI is disabled, so we can't display new output anyways.
                     Event::CommandOutput(_) => {}
                     // Already handled before.
-                    Event::TerminalResized => {}
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
                     // TUI should not be interactive while blocking.
-                    Event::KeyPressed(_) => {}
+                    Event::KeybindingPressed(..) | Event::KeyPressed(_) => {}
                     // Currently not blocking, so should never receive completed subcommand events.
-                    Event::SubcommandCompleted(_) | Event::SubcommandForEnvCompleted(_) => {}
+                    Event::SubcommandCompleted(_)
+                    | Event::SubcommandForEnvCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
+                },
+                BlockingState::BlockedReadingTextInput => match event {
+                    Event::CommandOutput(lines) => {
+                        // We handle new output lines, but don't exit the
+                        // blocking state.
+                        self.handle_command_output(lines)?;
+                    }
+                    Event::KeyPressed(key) => {
+                        match self.state.handle_text_input_key(&key).await {
+                            PromptState::Pending => {}
+                            PromptState::Completed => {
+                                self.set_key_forwarding(KeyForwarding::BoundKeys).await?;
+
+                                if let ControlFlow::Exit = self.conclude_blocking().await? {
+                                    break 'event_loop;
+                                }
+                            }
+                            PromptState::Aborted => {
+                                self.set_key_forwarding(KeyForwarding::BoundKeys).await?;
+
+                                // Abort all remaining operations.
+                                self.remaining_operations = None;
+
+                                if let ControlFlow::Exit = self.conclude_blocking().await? {
+                                    break 'event_loop;
+                                }
+                            }
+                        }
+                    }
+                    // Keys are interpreted as text input, not as keybindings.
+                    Event::KeybindingPressed(..) => {}
+                    // Already handled before.
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
+                    // Currently not waiting for any blocking subcommand to complete.
+                    Event::SubcommandCompleted(_)
+                    | Event::SubcommandForEnvCompleted(_)
+                    | Event::TUISubcommandCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
+                },
+                BlockingState::BlockedShowingError => match event {
+                    Event::CommandOutput(lines) => {
+                        // We handle new output lines, but don't exit the
+                        // blocking state.
+                        self.handle_command_output(lines)?;
+                    }
+                    Event::KeyPressed(_) => {
+                        // Any key dismisses the error.
+                        self.state.hide_error();
+                        self.set_key_forwarding(KeyForwarding::BoundKeys).await?;
+
+                        if let ControlFlow::Exit = self.conclude_blocking().await? {
+                            break 'event_loop;
+                        }
+                    }
+                    // Keys only dismiss the error, and don't trigger keybindings.
+                    Event::KeybindingPressed(..) => {}
+                    // Already handled before.
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
+                    // Currently not waiting for any blocking subcommand to complete.
+                    Event::SubcommandCompleted(_)
+                    | Event::SubcommandForEnvCompleted(_)
+                    | Event::TUISubcommandCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
+                },
+                BlockingState::BlockedShowingOutput => match event {
+                    Event::CommandOutput(lines) => {
+                        // We handle new output lines, but don't exit the
+                        // blocking state.
+                        self.handle_command_output(lines)?;
+                    }
+                    Event::KeyPressed(key) => {
+                        if self.state.handle_output_viewer_key(&key) {
+                            self.set_key_forwarding(KeyForwarding::BoundKeys).await?;
+
+                            if let ControlFlow::Exit = self.conclude_blocking().await? {
+                                break 'event_loop;
+                            }
+                        }
+                    }
+                    // Keys only scroll through the output, and don't trigger keybindings.
+                    Event::KeybindingPressed(..) => {}
+                    // Already handled before.
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
+                    // Currently not waiting for any blocking subcommand to complete.
+                    Event::SubcommandCompleted(_)
+                    | Event::SubcommandForEnvCompleted(_)
+                    | Event::TUISubcommandCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
                 },
                 BlockingState::BlockedReloadingWatchedCommand => match event {
                     Event::CommandOutput(lines) => {
                         // TODO: is called from async context, should be put in spawn_blocking
-                        self.state.update_lines(lines?)?;
+                        self.handle_command_output(lines)?;
 
                         if let ControlFlow::Exit = self.conclude_blocking().await? {
                             break 'event_loop;
                         }
                     }
                     // Already handled before.
-                    Event::TerminalResized => {}
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
                     // TUI should not be interactive while blocking.
-                    Event::KeyPressed(_) => {}
+                    Event::KeybindingPressed(..) | Event::KeyPressed(_) => {}
                     // Currently not waiting for any blocking subcommand to complete.
                     Event::SubcommandCompleted(_)
                     | Event::SubcommandForEnvCompleted(_)
-                    | Event::TUISubcommandCompleted(_) => {}
+                    | Event::TUISubcommandCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
                 },
                 BlockingState::BlockedExecutingSubcommand => match event {
                     Event::CommandOutput(lines) => {
                         if self.update_ui_while_blocking {
                             // We update the UI with the new output lines,
                             // but don't exit the blocking state.
-                            self.state.update_lines(lines?)?;
+                            self.handle_command_output(lines)?;
                         }
                     }
-                    Event::SubcommandCompleted(potential_error) => {
-                        potential_error?;
-
+                    Event::SubcommandCompleted(result) => {
                         // Remove temporary env vars that were added just for execution.
                         self.state.remove_cursor_and_selected_lines_from_env().await;
+                        self.conclude_subcommand().await?;
 
-                        if let ControlFlow::Exit = self.conclude_blocking().await? {
+                        let control_flow = match result {
+                            Ok(()) => self.conclude_blocking().await?,
+                            Err(error) => self.handle_subcommand_failure(error).await?,
+                        };
+                        if let ControlFlow::Exit = control_flow {
+                            break 'event_loop;
+                        }
+                    }
+                    Event::PopupSubcommandCompleted(result) => {
+                        // Remove temporary env vars that were added just for execution.
+                        self.state.remove_cursor_and_selected_lines_from_env().await;
+                        self.conclude_subcommand().await?;
+
+                        let control_flow = match result {
+                            Ok(Some(output)) => self.show_output(output).await?,
+                            // The subcommand was cancelled, so there is no output to show.
+                            Ok(None) => self.conclude_blocking().await?,
+                            Err(error) => self.handle_subcommand_failure(error).await?,
+                        };
+                        if let ControlFlow::Exit = control_flow {
                             break 'event_loop;
                         }
                     }
+                    Event::KeyPressed(key) => {
+                        if key == self.cancel_key {
+                            self.cancel_subcommand().await;
+                        }
+                    }
                     // Already handled before.
-                    Event::TerminalResized => {}
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
                     // TUI should not be interactive while blocking.
-                    Event::KeyPressed(_) => {}
+                    Event::KeybindingPressed(..) => {}
                     // Currently not waiting for any blocking subcommand to complete.
                     Event::SubcommandForEnvCompleted(_) | Event::TUISubcommandCompleted(_) => {}
                 },
@@ -349,24 +679,39 @@ impl UI {
                     Event::CommandOutput(lines) => {
                         // We handle new output lines, but don't exit the
                         // blocking state.
-                        self.state.update_lines(lines?)?;
+                        self.handle_command_output(lines)?;
                     }
-                    Event::SubcommandForEnvCompleted(new_env_variables) => {
+                    Event::SubcommandForEnvCompleted(result) => {
                         // Remove temporary env vars that were added just for execution.
                         self.state.remove_cursor_and_selected_lines_from_env().await;
+                        self.conclude_subcommand().await?;
 
-                        self.state.set_envs(new_env_variables?).await;
-
-                        if let ControlFlow::Exit = self.conclude_blocking().await? {
+                        let control_flow = match result {
+                            Ok(new_env_variables) => {
+                                self.state.set_envs(new_env_variables).await;
+                                self.conclude_blocking().await?
+                            }
+                            Err(error) => self.handle_subcommand_failure(error).await?,
+                        };
+                        if let ControlFlow::Exit = control_flow {
                             break 'event_loop;
                         }
                     }
+                    Event::KeyPressed(key) => {
+                        if key == self.cancel_key {
+                            self.cancel_subcommand().await;
+                        }
+                    }
                     // Already handled before.
-                    Event::TerminalResized => {}
+                    Event::TerminalResized
+                    | Event::MousePressed { .. }
+                    | Event::WatchedCommandExited(_) => {}
                     // TUI should not be interactive while blocking.
-                    Event::KeyPressed(_) => {}
+                    Event::KeybindingPressed(..) => {}
                     // Currently not waiting for any blocking subcommand to complete.
-                    Event::SubcommandCompleted(_) | Event::TUISubcommandCompleted(_) => {}
+                    Event::SubcommandCompleted(_)
+                    | Event::TUISubcommandCompleted(_)
+                    | Event::PopupSubcommandCompleted(_) => {}
                 },
             };
         }
@@ -379,15 +724,21 @@ impl UI {
     /// operations, we update the remaining operations.
     async fn handle_key_event_given_starting_index(
         &mut self,
-        key: KeyEvent,
+        mode: Option<ModeName>,
+        key: KeySequence,
         starting_index: usize,
     ) -> Result<ControlFlow> {
-        if let Some(ops) = self.keybindings.get_operations(&key) {
+        let keybindings = Arc::clone(&self.keybindings);
+        if let Some(ops) = keybindings.get(mode.as_ref()).get_operations(&key) {
             for (idx, op) in ops.into_iter().enumerate().skip(starting_index) {
-                match op
+                let requested_action = match op
                     .execute(&mut self.state, &self.channels.event_tx, &key)
-                    .await?
+                    .await
                 {
+                    Ok(requested_action) => requested_action,
+                    Err(error) => return self.handle_subcommand_failure(error).await,
+                };
+                match requested_action {
                     RequestedAction::Exit => return Ok(ControlFlow::Exit),
                     RequestedAction::ReloadWatchedCommand => {
                         // Send the command execution an interrupt signal
@@ -396,19 +747,26 @@ impl UI {
                             return Ok(ControlFlow::Exit);
                         }
 
-                        save_remaining_operations!(self, key, idx + 1, ops);
+                        save_remaining_operations!(self, mode, key, idx + 1, ops);
+                        self.state.set_activity(Some(op.to_string()));
                         self.blocking_state = BlockingState::BlockedReloadingWatchedCommand;
 
                         return Ok(ControlFlow::Continue);
                     }
-                    RequestedAction::ExecutingBlockingSubcommand => {
-                        save_remaining_operations!(self, key, idx + 1, ops);
+                    RequestedAction::ExecutingBlockingSubcommand(interrupt_tx) => {
+                        self.start_subcommand(interrupt_tx).await?;
+
+                        save_remaining_operations!(self, mode, key, idx + 1, ops);
+                        self.state.set_activity(Some(op.to_string()));
                         self.blocking_state = BlockingState::BlockedExecutingSubcommand;
 
                         return Ok(ControlFlow::Continue);
                     }
-                    RequestedAction::ExecutingBlockingSubcommandForEnv => {
-                        save_remaining_operations!(self, key, idx + 1, ops);
+                    RequestedAction::ExecutingBlockingSubcommandForEnv(interrupt_tx) => {
+                        self.start_subcommand(interrupt_tx).await?;
+
+                        save_remaining_operations!(self, mode, key, idx + 1, ops);
+                        self.state.set_activity(Some(op.to_string()));
                         self.blocking_state = BlockingState::BlockedExecutingSubcommandForEnv;
 
                         return Ok(ControlFlow::Continue);
@@ -420,11 +778,35 @@ impl UI {
                         tui_hidden_tx.send(()).await?;
                         log::info!("Watchbind's TUI has been hidden.");
 
-                        save_remaining_operations!(self, key, idx + 1, ops);
+                        save_remaining_operations!(self, mode, key, idx + 1, ops);
+                        self.state.set_activity(Some(op.to_string()));
                         self.blocking_state = BlockingState::BlockedExecutingTUISubcommand;
 
                         return Ok(ControlFlow::Continue);
                     }
+                    RequestedAction::ReadingTextInput => {
+                        // The text input must receive all key presses, not
+                        // only those that are part of a keybinding.
+                        self.set_key_forwarding(KeyForwarding::AllKeys).await?;
+
+                        save_remaining_operations!(self, mode, key, idx + 1, ops);
+                        // The text input itself shows what is blocking.
+                        self.state.set_activity(None);
+                        self.blocking_state = BlockingState::BlockedReadingTextInput;
+
+                        return Ok(ControlFlow::Continue);
+                    }
+                    RequestedAction::ChangeWatching(change) => {
+                        if let ControlFlow::Exit = self.change_watching(change).await? {
+                            return Ok(ControlFlow::Exit);
+                        }
+                        draw!(self)?;
+                    }
+                    RequestedAction::ChangedMode => {
+                        self.set_polling_mode(self.state.active_mode().cloned())
+                            .await?;
+                        draw!(self)?;
+                    }
                     RequestedAction::Continue => {
                         // Redraw the UI between the execution of each
                         // non-blocking operation.
@@ -433,11 +815,40 @@ impl UI {
                 };
             }
 
+            self.state.set_activity(None);
             self.blocking_state = BlockingState::Unblocked;
         }
         Ok(ControlFlow::Continue)
     }
 
+    /// Keep the `interrupt_tx` of a blocking subcommand that has started
+    /// executing, so that it can be cancelled.
+    async fn start_subcommand(&mut self, interrupt_tx: Sender<InterruptSignal>) -> Result<()> {
+        self.subcommand_interrupt_tx = Some(interrupt_tx);
+
+        // The cancel key must be received, even if it is not part of a
+        // keybinding.
+        self.set_key_forwarding(KeyForwarding::AllKeys).await
+    }
+
+    /// Cancel the blocking subcommand that is currently executing, and abort
+    /// all remaining operations. We keep blocking until the subcommand has
+    /// actually been killed and completes.
+    async fn cancel_subcommand(&mut self) {
+        if let Some(interrupt_tx) = self.subcommand_interrupt_tx.take() {
+            log::info!("Cancelling blocking subcommand");
+            self.remaining_operations = None;
+            // The subcommand might have completed in the meantime.
+            let _ = interrupt_tx.send(InterruptSignal).await;
+        }
+    }
+
+    /// Clean up after a blocking subcommand has completed.
+    async fn conclude_subcommand(&mut self) -> Result<()> {
+        self.subcommand_interrupt_tx = None;
+        self.set_key_forwarding(KeyForwarding::BoundKeys).await
+    }
+
     /// Tells the terminal event listener thread to stop polling.
     async fn pause_terminal_events_polling(&self) -> Result<()> {
         // Create channels for waiting until polling has actually been paused.
@@ -454,6 +865,112 @@ impl UI {
         Ok(())
     }
 
+    /// Tells the terminal event listener thread which key presses to forward.
+    async fn set_key_forwarding(&self, key_forwarding: KeyForwarding) -> Result<()> {
+        self.channels
+            .polling_tx
+            .send(PollingCommand::SetKeyForwarding(key_forwarding))
+            .await?;
+        Ok(())
+    }
+
+    /// Handle the `error` of a failed subcommand. Unless configured to exit on
+    /// failure, the error is displayed until dismissed by the user, and all
+    /// remaining operations are aborted. Errors that aren't caused by a
+    /// subcommand failing, e.g. internal errors of an operation, are returned.
+    async fn handle_subcommand_failure(&mut self, error: anyhow::Error) -> Result<ControlFlow> {
+        if self.exit_on_subcommand_failure || !is_command_failure(&error) {
+            return Err(error);
+        }
+        log::error!("{:?}", error);
+
+        // Abort all remaining operations.
+        self.remaining_operations = None;
+
+        self.state.set_activity(None);
+        self.state.show_error(&error);
+
+        // Any key press should dismiss the error, not only those that are
+        // part of a keybinding.
+        self.set_key_forwarding(KeyForwarding::AllKeys).await?;
+        self.blocking_state = BlockingState::BlockedShowingError;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// Display the new output of the watched command. If the watched command
+    /// failed, its previous output stays displayed together with an error
+    /// banner, until it succeeds again.
+    fn handle_command_output(&mut self, output: Result<String>) -> Result<()> {
+        match output {
+            Ok(lines) => {
+                self.state.hide_command_error();
+                self.state.update_lines(lines)
+            }
+            Err(error) => {
+                if self.exit_on_watched_command_failure {
+                    return Err(error);
+                }
+                log::error!("{:?}", error);
+                self.state.show_command_error(&error);
+                Ok(())
+            }
+        }
+    }
+
+    /// Show the `output` of a subcommand in a popup, which blocks until it is
+    /// dismissed by the user.
+    async fn show_output(&mut self, output: SubcommandOutput) -> Result<ControlFlow> {
+        self.state.set_activity(None);
+        self.state.show_output(output);
+
+        // All keys should scroll through or dismiss the output, not only
+        // those that are part of a keybinding.
+        self.set_key_forwarding(KeyForwarding::AllKeys).await?;
+        self.blocking_state = BlockingState::BlockedShowingOutput;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// Apply the `change` to the periodic execution of the watched command,
+    /// and tell the command polling thread about it.
+    async fn change_watching(&mut self, change: WatchChange) -> Result<ControlFlow> {
+        let command = match change {
+            WatchChange::Pause => {
+                self.paused = true;
+                WatchCommand::SetPaused(true)
+            }
+            WatchChange::Resume => {
+                self.paused = false;
+                WatchCommand::SetPaused(false)
+            }
+            WatchChange::TogglePause => {
+                self.paused = !self.paused;
+                WatchCommand::SetPaused(self.paused)
+            }
+            WatchChange::Interval(interval_change) => {
+                self.watch_rate = interval_change.apply(self.watch_rate);
+                WatchCommand::SetWatchRate(self.watch_rate)
+            }
+        };
+        self.state.set_watching(self.paused, self.watch_rate);
+
+        if self.channels.watch_tx.send(command).await.is_err() {
+            return Ok(ControlFlow::Exit);
+        }
+        Ok(ControlFlow::Continue)
+    }
+
+    /// Tells the terminal event listener thread which mode's keybindings to
+    /// use.
+    async fn set_polling_mode(&self, mode: Option<ModeName>) -> Result<()> {
+        self.channels
+            .polling_tx
+            .send(PollingCommand::SetMode(mode))
+            .await?;
+        Ok(())
+    }
+
     /// Remove all elements from the events channel.
     fn clear_events_channel(&mut self) {
         clear_buffer(&mut self.channels.event_rx);
@@ -469,24 +986,31 @@ impl UI {
 
         match self.remaining_operations.take() {
             Some(RemainingOperations {
+                mode,
                 key,
                 remaining_index,
             }) => {
                 // Execute any remaining operations.
-                self.handle_key_event_given_starting_index(key, remaining_index)
+                self.handle_key_event_given_starting_index(mode, key, remaining_index)
                     .await
             }
             None => {
                 // Given no more remaining operations, we can unblock.
+                self.state.set_activity(None);
                 self.blocking_state = BlockingState::Unblocked;
                 Ok(ControlFlow::Continue)
             }
         }
     }
 
-    /// Execute the operations associated with a key event.
-    async fn handle_key_event(&mut self, key: KeyEvent) -> Result<ControlFlow> {
-        self.handle_key_event_given_starting_index(key, 0).await
+    /// Execute the operations associated with a key sequence.
+    async fn handle_key_event(
+        &mut self,
+        mode: Option<ModeName>,
+        key: KeySequence,
+    ) -> Result<ControlFlow> {
+        self.handle_key_event_given_starting_index(mode, key, 0)
+            .await
     }
 }
 
@@ -494,54 +1018,358 @@ impl UI {
 /// command execution should be reloaded.
 pub struct InterruptSignal;
 
+/// A command sent to the command polling thread, which changes how the
+/// watched command is periodically executed.
+enum WatchCommand {
+    /// Pause or resume the periodic execution. Reloads are still executed
+    /// while paused.
+    SetPaused(bool),
+    /// Change the duration between executions.
+    SetWatchRate(Duration),
+}
+
 /// Continuously executes the command in a loop, separated by sleeps of
 /// watch_rate duration. Additionally, can be signalled to reload the execution
-/// of the command, which simply wakes up this thread sooner.
+/// of the command, which simply wakes up this thread sooner. The periodic
+/// execution can be paused and the watch_rate changed through `watch_rx`.
 /// The stdout of successful executions is sent back to the main thread.
 async fn poll_execute_watched_command(
     mut watched_command: WatchedCommand,
-    watch_rate: Duration,
+    mut watch_rate: Duration,
     event_tx: Sender<Event>,
+    mut watch_rx: Receiver<WatchCommand>,
 ) {
-    loop {
+    let mut paused = false;
+
+    'execution: loop {
         let start_time = Instant::now();
 
-        let output_lines_result = match watched_command.execute().await {
+        let (output_lines_result, exit_code) = match watched_command.execute().await {
             Ok(ExecutionResult::Interrupted) => continue,
-            Ok(ExecutionResult::Stdout(output_lines)) => Ok(output_lines),
-            Err(e) => Err(e),
+            Ok(ExecutionResult::Stdout { stdout, exit_code }) => (Ok(stdout), exit_code),
+            Err(e) => {
+                let exit_code = ExitError::exit_code_of(&e);
+                (Err(e), exit_code)
+            }
         };
 
         if event_tx
-            .send(Event::CommandOutput(output_lines_result))
+            .send(Event::WatchedCommandExited(exit_code))
             .await
             .is_err()
+            || event_tx
+                .send(Event::CommandOutput(output_lines_result))
+                .await
+                .is_err()
         {
             break;
         };
 
-        // If all senders (i.e. the main thread) have been dropped, we abort.
-        if watch_rate == Duration::ZERO {
-            // Wake up only when notified.
-            let WasWoken::ReceivedInterrupt = watched_command.wait_for_interrupt().await else {
-                break;
-            };
-        } else {
-            // Wake up at the earliest when notified through recv, or at
-            // latest after the watch_rate timeout has passed.
-            let timeout = watch_rate.saturating_sub(start_time.elapsed());
-            let WasWoken::ReceivedInterrupt = watched_command
-                .wait_for_interrupt_within_timeout(timeout)
-                .await
-            else {
-                break;
+        // Wait again whenever the watch settings have changed, because the
+        // changed settings might require waiting for a different duration.
+        loop {
+            // Wake up only when notified if periodic execution is disabled.
+            // Otherwise, wake up at the earliest when notified through recv,
+            // or at latest after the watch_rate timeout has passed.
+            let timeout = (!paused && watch_rate != Duration::ZERO)
+                .then(|| watch_rate.saturating_sub(start_time.elapsed()));
+            let wait_for_interrupt = async {
+                match timeout {
+                    Some(timeout) => {
+                        watched_command
+                            .wait_for_interrupt_within_timeout(timeout)
+                            .await
+                    }
+                    None => watched_command.wait_for_interrupt().await,
+                }
             };
+
+            tokio::select! {
+                was_woken = wait_for_interrupt => match was_woken {
+                    WasWoken::ReceivedInterrupt => continue 'execution,
+                    // If all senders (i.e. the main thread) have been dropped,
+                    // we abort.
+                    WasWoken::ChannelClosed => break 'execution,
+                },
+                watch_command = watch_rx.recv() => match watch_command {
+                    Some(WatchCommand::SetPaused(new_paused)) => paused = new_paused,
+                    Some(WatchCommand::SetWatchRate(new_watch_rate)) => {
+                        watch_rate = new_watch_rate;
+                    }
+                    None => break 'execution,
+                },
+            }
         }
     }
 
     log::info!("Shutting down command executor task");
 }
 
+/// The minimum duration between two updates of the UI with new streamed
+/// lines, so that fast output doesn't cause a redraw for every single line.
+const STREAM_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The most recent lines streamed from a line source, which are sent to the
+/// main thread in batches.
+struct StreamedLines {
+    lines: VecDeque<String>,
+    /// The maximum number of lines kept, after which the oldest lines are
+    /// dropped.
+    max_lines: usize,
+    /// Whether there are changes that haven't been sent yet.
+    unsent: bool,
+    /// The earliest time the next batch may be sent at.
+    next_update: Instant,
+}
+
+impl StreamedLines {
+    fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max_lines,
+            unsent: false,
+            next_update: Instant::now(),
+        }
+    }
+
+    /// Add a new `line`, dropping the oldest line if there are too many.
+    fn push(&mut self, line: String) {
+        self.lines.push_back(line);
+        if self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+        self.unsent = true;
+    }
+
+    /// Send the lines as soon as possible, e.g. because no more lines will
+    /// arrive.
+    fn update_now(&mut self) {
+        self.unsent = true;
+        self.next_update = Instant::now();
+    }
+
+    /// Whether a batch should be sent once `next_update` is reached.
+    fn should_update(&self, paused: bool) -> bool {
+        self.unsent && !paused
+    }
+
+    /// Get all lines as output to be sent, and mark them as sent.
+    fn take_output(&mut self) -> String {
+        self.unsent = false;
+        self.next_update = Instant::now() + STREAM_UPDATE_INTERVAL;
+        self.lines.iter().join("\n")
+    }
+}
+
+/// Executes the command once and streams its output line by line, keeping at
+/// most `max_lines` of the most recent lines. The accumulated output is sent
+/// whenever new lines arrive, but at most once per `STREAM_UPDATE_INTERVAL`
+/// and not while paused. Reloading restarts the command with empty output.
+async fn poll_stream_watched_command(
+    mut watched_command: WatchedCommand,
+    max_lines: usize,
+    event_tx: Sender<Event>,
+    mut watch_rx: Receiver<WatchCommand>,
+) {
+    let mut paused = false;
+
+    'execution: loop {
+        let mut lines = StreamedLines::new(max_lines);
+
+        // Reset the output, which also concludes a reload.
+        if event_tx
+            .send(Event::CommandOutput(Ok(String::new())))
+            .await
+            .is_err()
+        {
+            break;
+        }
+
+        // A command that failed to spawn can still be reloaded.
+        let mut child = match watched_command.spawn_streaming().await {
+            Ok(child) => Some(child),
+            Err(e) => {
+                if event_tx.send(Event::CommandOutput(Err(e))).await.is_err() {
+                    break;
+                }
+                None
+            }
+        };
+
+        loop {
+            tokio::select! {
+                line = next_streamed_line(&mut child) => match line {
+                    Ok(Some(line)) => lines.push(line),
+                    // Stdout has been closed, so the command is exiting.
+                    Ok(None) => {
+                        let exit_result = match child.take() {
+                            Some(child) => watched_command.wait_streaming(child).await,
+                            None => Ok(None),
+                        };
+                        let exit_code = match &exit_result {
+                            Ok(exit_code) => *exit_code,
+                            Err(e) => ExitError::exit_code_of(e),
+                        };
+                        if event_tx.send(Event::WatchedCommandExited(exit_code)).await.is_err() {
+                            break 'execution;
+                        }
+                        match exit_result {
+                            Ok(_) => lines.update_now(),
+                            Err(e) => {
+                                // Send the last lines before the error, so
+                                // that they are displayed together.
+                                let output = lines.take_output();
+                                if event_tx.send(Event::CommandOutput(Ok(output))).await.is_err()
+                                    || event_tx.send(Event::CommandOutput(Err(e))).await.is_err()
+                                {
+                                    break 'execution;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        // Dropping the child kills it, but it can still be
+                        // reloaded.
+                        child = None;
+                        if event_tx.send(Event::CommandOutput(Err(e))).await.is_err() {
+                            break 'execution;
+                        }
+                    }
+                },
+                _ = tokio::time::sleep_until(lines.next_update.into()), if lines.should_update(paused) => {
+                    let output = lines.take_output();
+                    if event_tx.send(Event::CommandOutput(Ok(output))).await.is_err() {
+                        break 'execution;
+                    }
+                },
+                was_woken = watched_command.wait_for_interrupt() => match was_woken {
+                    WasWoken::ReceivedInterrupt => {
+                        if let Some(child) = &mut child {
+                            if let Err(e) = child.kill().await {
+                                log::error!("Killing the streamed watched command failed with: {}", e);
+                            }
+                        }
+                        continue 'execution;
+                    }
+                    // If all senders (i.e. the main thread) have been dropped,
+                    // we abort.
+                    WasWoken::ChannelClosed => break 'execution,
+                },
+                watch_command = watch_rx.recv() => match watch_command {
+                    Some(WatchCommand::SetPaused(new_paused)) => paused = new_paused,
+                    // The command is executed only once, so there is no interval.
+                    Some(WatchCommand::SetWatchRate(_)) => {}
+                    None => break 'execution,
+                },
+            }
+        }
+    }
+
+    log::info!("Shutting down command streaming task");
+}
+
+/// Read the next line streamed from the `child`, or wait forever if there is
+/// no child anymore.
+async fn next_streamed_line(child: &mut Option<StreamingChild>) -> Result<Option<String>> {
+    match child {
+        Some(child) => child.next_line().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Streams the lines piped into stdin, keeping at most `max_lines` of the
+/// most recent lines, like `poll_stream_watched_command`. Since the lines
+/// can't be read again, reloading only sends the lines read so far.
+async fn poll_read_stdin(
+    max_lines: usize,
+    event_tx: Sender<Event>,
+    reload_rx: Receiver<InterruptSignal>,
+    watch_rx: Receiver<WatchCommand>,
+) {
+    // Tokio's stdin is read on a blocking thread that can't be cancelled,
+    // which would prevent exiting while the writing end of the pipe is still
+    // open, so we read stdin on a separate thread that doesn't outlive us.
+    let (line_tx, line_rx) = mpsc::channel(STDIN_LINES_CHANNEL_CAPACITY);
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let line = line.context("Failed to read line from stdin");
+            let failed = line.is_err();
+            if line_tx.blocking_send(line).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    stream_read_lines(line_rx, max_lines, event_tx, reload_rx, watch_rx).await;
+
+    log::info!("Shutting down stdin reading task");
+}
+
+/// Streams the lines received through `line_rx`, until all senders of the
+/// main thread have been dropped. Reading stops at the first error, but the
+/// lines read so far can still be reloaded.
+async fn stream_read_lines(
+    mut line_rx: Receiver<Result<String>>,
+    max_lines: usize,
+    event_tx: Sender<Event>,
+    mut reload_rx: Receiver<InterruptSignal>,
+    mut watch_rx: Receiver<WatchCommand>,
+) {
+    let mut lines = StreamedLines::new(max_lines);
+    let mut paused = false;
+    let mut lines_closed = false;
+
+    loop {
+        tokio::select! {
+            line = line_rx.recv(), if !lines_closed => match line {
+                Some(Ok(line)) => lines.push(line),
+                Some(Err(e)) => {
+                    lines_closed = true;
+                    // Send the last lines before the error, so that they are
+                    // displayed together.
+                    let output = lines.take_output();
+                    if event_tx.send(Event::CommandOutput(Ok(output))).await.is_err()
+                        || event_tx.send(Event::CommandOutput(Err(e))).await.is_err()
+                    {
+                        break;
+                    }
+                }
+                None => {
+                    lines_closed = true;
+                    lines.update_now();
+                }
+            },
+            _ = tokio::time::sleep_until(lines.next_update.into()), if lines.should_update(paused) => {
+                let output = lines.take_output();
+                if event_tx.send(Event::CommandOutput(Ok(output))).await.is_err() {
+                    break;
+                }
+            },
+            interrupt = reload_rx.recv() => match interrupt {
+                Some(InterruptSignal) => {
+                    let output = lines.take_output();
+                    if event_tx.send(Event::CommandOutput(Ok(output))).await.is_err() {
+                        break;
+                    }
+                }
+                // If all senders (i.e. the main thread) have been dropped, we
+                // abort.
+                None => break,
+            },
+            watch_command = watch_rx.recv() => match watch_command {
+                Some(WatchCommand::SetPaused(new_paused)) => paused = new_paused,
+                // The lines aren't read periodically, so there is no interval.
+                Some(WatchCommand::SetWatchRate(_)) => {}
+                None => break,
+            },
+        }
+    }
+}
+
+/// The number of lines read from stdin that can be buffered before reading
+/// more lines waits for them to be processed.
+const STDIN_LINES_CHANNEL_CAPACITY: usize = 1000;
+
 /// A command sent to a polling thread.
 enum PollingCommand {
     /// Continue listening/polling for terminal events.
@@ -549,20 +1377,123 @@ enum PollingCommand {
     /// Pause listening/polling for terminal events. Notifies event's sender
     /// once polling has actually been paused.
     Pause(Sender<PollingPaused>),
+    /// Change which key presses are forwarded to the main thread.
+    SetKeyForwarding(KeyForwarding),
+    /// Change the mode whose keybindings key presses are matched against.
+    SetMode(Option<ModeName>),
+}
+
+/// Which key presses are forwarded from the terminal event listener to the
+/// main thread.
+#[derive(Clone, Copy)]
+enum KeyForwarding {
+    /// Only forward key presses that are part of a keybinding.
+    BoundKeys,
+    /// Forward all key presses, e.g. while the user is typing into a text
+    /// input.
+    AllKeys,
 }
 
 /// A message, sent via a channel, that the polling has been paused.
 struct PollingPaused;
 
+/// The keys pressed so far of a key sequence that has not been completed yet.
+struct PendingKeySequence {
+    keys: Vec<KeyEvent>,
+    /// The maximum duration to wait for the next key of a key sequence.
+    timeout: Duration,
+    /// The point in time at which the pending keys expire, or `None` if there
+    /// are no pending keys.
+    deadline: Option<tokio::time::Instant>,
+}
+
+impl PendingKeySequence {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            keys: vec![],
+            timeout,
+            deadline: None,
+        }
+    }
+
+    /// Add a newly pressed key. Returns the key sequences of all keybindings
+    /// that have been completed by pressing it.
+    fn push(&mut self, key: KeyEvent, keybindings: &Keybindings) -> Vec<KeySequence> {
+        self.keys.push(key);
+
+        let mut completed = vec![];
+        loop {
+            let key_sequence = KeySequence::from(self.keys.clone());
+            match keybindings.match_key_sequence(&key_sequence) {
+                KeySequenceMatch::Complete => {
+                    completed.push(key_sequence);
+                    self.clear();
+                    break;
+                }
+                KeySequenceMatch::Ambiguous | KeySequenceMatch::Partial => {
+                    // Wait for the next key to resolve the key sequence.
+                    self.deadline = Some(tokio::time::Instant::now() + self.timeout);
+                    break;
+                }
+                KeySequenceMatch::NoMatch => {
+                    // The newest key doesn't continue the previous keys, so
+                    // conclude the previous keys, and retry the newest key on
+                    // its own.
+                    let newest_key = self.keys.pop().expect("pushed key should exist");
+                    if self.keys.is_empty() {
+                        self.clear();
+                        break;
+                    }
+                    let previous = KeySequence::from(std::mem::take(&mut self.keys));
+                    if keybindings.get_operations(&previous).is_some() {
+                        completed.push(previous);
+                    }
+                    self.keys.push(newest_key);
+                }
+            }
+        }
+        completed
+    }
+
+    /// The pending keys have expired, since the next key wasn't pressed in
+    /// time. Returns the key sequence of the pending keys if it belongs to a
+    /// keybinding.
+    fn expire(&mut self, keybindings: &Keybindings) -> Option<KeySequence> {
+        let key_sequence = KeySequence::from(std::mem::take(&mut self.keys));
+        self.clear();
+        keybindings
+            .get_operations(&key_sequence)
+            .is_some()
+            .then_some(key_sequence)
+    }
+
+    /// Discard all pending keys.
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.deadline = None;
+    }
+}
+
 /// Continuously listens for terminal-related events, and sends relevant events
 /// back to the main thread.
-/// For key events, only those that are part of a keybinding are sent.
+/// For key events, only completed key sequences of keybindings are sent,
+/// unless instructed to forward all key events.
 /// For terminal resizing, we always notify.
 async fn poll_terminal_events(
-    keybindings: Arc<Keybindings>,
+    keybindings: Arc<ModalKeybindings>,
+    key_sequence_timeout: Duration,
     event_tx: Sender<Event>,
     mut polling_rx: Receiver<PollingCommand>,
 ) {
+    let mut key_forwarding = KeyForwarding::BoundKeys;
+    let mut pending_keys = PendingKeySequence::new(key_sequence_timeout);
+    // The mode is only updated once the main thread has executed a mode
+    // operation and sent `SetMode`, so keys pressed in between are still
+    // matched against the previous mode's keybindings. The main thread looks
+    // up the operations of such key sequences in the active mode, but keys
+    // that aren't bound in the previous mode are not recognized.
+    let mut mode: Option<ModeName> = None;
+
     'main_loop: loop {
         // Poll terminal events until instructed to pause.
         let polling_paused_tx = {
@@ -571,15 +1502,38 @@ async fn poll_terminal_events(
             let mut terminal_event_reader = EventStream::new();
 
             'polling_loop: loop {
+                let pending_keys_deadline = pending_keys.deadline;
+
                 tokio::select! {
                     // Wait for receival of a polling command from main event loop thread.
                     polling = polling_rx.recv() => match polling {
-                        Some(PollingCommand::Pause(polling_paused_tx)) => break 'polling_loop polling_paused_tx,
+                        Some(PollingCommand::Pause(polling_paused_tx)) => {
+                            pending_keys.clear();
+                            break 'polling_loop polling_paused_tx;
+                        }
                         // Currently already listening for terminal events.
                         Some(PollingCommand::Listen) => continue 'polling_loop,
+                        Some(PollingCommand::SetKeyForwarding(new_key_forwarding)) => {
+                            key_forwarding = new_key_forwarding;
+                            pending_keys.clear();
+                            continue 'polling_loop;
+                        }
+                        Some(PollingCommand::SetMode(new_mode)) => {
+                            mode = new_mode;
+                            pending_keys.clear();
+                            continue 'polling_loop;
+                        }
                         // Channel has been closed.
                         None => break 'main_loop,
                     },
+                    // Wait until the pending keys expire.
+                    _ = tokio::time::sleep_until(pending_keys_deadline.unwrap_or_else(tokio::time::Instant::now)), if pending_keys_deadline.is_some() => {
+                        if let Some(key_sequence) = pending_keys.expire(keybindings.get(mode.as_ref())) {
+                            if event_tx.send(Event::KeybindingPressed(key_sequence)).await.is_err() {
+                                break 'main_loop;
+                            };
+                        }
+                    },
                     // Wait for a terminal event.
                     Some(Ok(event)) = terminal_event_reader.next().fuse() => match event {
                         // Only react to key press, otherwise we might react
@@ -588,13 +1542,38 @@ async fn poll_terminal_events(
                             if let Ok(key) = key_event.try_into() {
                                 log::info!("Key pressed: {}", key);
 
-                                if keybindings.get_operations(&key).is_some() {
-                                    // Ideally, we would send the &Operations directly, instead
-                                    // of only sending the key event, which the main thread
-                                    // then has to look-up again in the Keybindings hashmap,
-                                    // but sending references is infeasible (a lot of
-                                    // synchronization overhead).
-                                    if event_tx.send(Event::KeyPressed(key)).await.is_err() {
+                                match key_forwarding {
+                                    KeyForwarding::BoundKeys => {
+                                        // Ideally, we would send the &Operations directly, instead
+                                        // of only sending the key sequence, which the main thread
+                                        // then has to look-up again in the Keybindings hashmap,
+                                        // but sending references is infeasible (a lot of
+                                        // synchronization overhead).
+                                        for key_sequence in pending_keys.push(key, keybindings.get(mode.as_ref())) {
+                                            if event_tx.send(Event::KeybindingPressed(key_sequence)).await.is_err() {
+                                                break 'main_loop;
+                                            };
+                                        }
+                                    }
+                                    KeyForwarding::AllKeys => {
+                                        if event_tx.send(Event::KeyPressed(key)).await.is_err() {
+                                            break 'main_loop;
+                                        };
+                                    }
+                                }
+                            }
+                        }
+                        // Mouse events can only trigger keybindings.
+                        CrosstermEvent::Mouse(mouse_event) if matches!(key_forwarding, KeyForwarding::BoundKeys) => {
+                            if let Some(key) = KeyEvent::from_mouse_event(mouse_event) {
+                                log::info!("Mouse pressed: {}", key);
+
+                                let mouse_pressed = Event::MousePressed { row: mouse_event.row };
+                                if event_tx.send(mouse_pressed).await.is_err() {
+                                    break 'main_loop;
+                                };
+                                for key_sequence in pending_keys.push(key, keybindings.get(mode.as_ref())) {
+                                    if event_tx.send(Event::KeybindingPressed(key_sequence)).await.is_err() {
                                         break 'main_loop;
                                     };
                                 }
@@ -618,8 +1597,13 @@ async fn poll_terminal_events(
 
         // Wait until another Listen command is received.
         'wait_for_listen: while let Some(polling) = polling_rx.recv().await {
-            if let PollingCommand::Listen = polling {
-                break 'wait_for_listen;
+            match polling {
+                PollingCommand::Listen => break 'wait_for_listen,
+                PollingCommand::SetKeyForwarding(new_key_forwarding) => {
+                    key_forwarding = new_key_forwarding;
+                }
+                PollingCommand::SetMode(new_mode) => mode = new_mode,
+                PollingCommand::Pause(_) => {}
             }
         }
 
@@ -636,3 +1620,70 @@ async fn poll_terminal_events(
 fn clear_buffer<T>(rx: &mut Receiver<T>) {
     while rx.try_recv().is_ok() {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    /// Receive the next output sent to the main thread.
+    async fn next_output(event_rx: &mut Receiver<Event>) -> Result<String> {
+        match event_rx.recv().await {
+            Some(Event::CommandOutput(output)) => output,
+            _ => panic!("expected command output"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_read_lines_after_read_error() {
+        let (line_tx, line_rx) = mpsc::channel(10);
+        let (event_tx, mut event_rx) = mpsc::channel(10);
+        let (reload_tx, reload_rx) = mpsc::channel(1);
+        let (watch_tx, watch_rx) = mpsc::channel(1);
+        let task = tokio::spawn(stream_read_lines(
+            line_rx, 10, event_tx, reload_rx, watch_rx,
+        ));
+
+        line_tx.send(Ok("first".to_owned())).await.unwrap();
+        assert_eq!(next_output(&mut event_rx).await.unwrap(), "first");
+        line_tx.send(Err(anyhow!("read error"))).await.unwrap();
+        assert_eq!(next_output(&mut event_rx).await.unwrap(), "first");
+        assert!(next_output(&mut event_rx).await.is_err());
+
+        // The lines read so far can still be reloaded, even while paused.
+        watch_tx.send(WatchCommand::SetPaused(true)).await.unwrap();
+        reload_tx.send(InterruptSignal).await.unwrap();
+        assert_eq!(next_output(&mut event_rx).await.unwrap(), "first");
+        assert!(!task.is_finished());
+
+        drop(reload_tx);
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_read_lines_after_closed() {
+        let (line_tx, line_rx) = mpsc::channel(10);
+        let (event_tx, mut event_rx) = mpsc::channel(10);
+        let (reload_tx, reload_rx) = mpsc::channel(1);
+        let (_watch_tx, watch_rx) = mpsc::channel(1);
+        let task = tokio::spawn(stream_read_lines(line_rx, 2, event_tx, reload_rx, watch_rx));
+
+        for line in ["first", "second", "third"] {
+            line_tx.send(Ok(line.to_owned())).await.unwrap();
+        }
+        drop(line_tx);
+        // Lines may be sent in multiple batches, but the last batch contains
+        // the most recent lines.
+        let mut output = String::new();
+        while !output.contains("third") {
+            output = next_output(&mut event_rx).await.unwrap();
+        }
+        assert_eq!(output, "second\nthird");
+
+        reload_tx.send(InterruptSignal).await.unwrap();
+        assert_eq!(next_output(&mut event_rx).await.unwrap(), "second\nthird");
+
+        drop(reload_tx);
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,76 @@
+use ratatui::{
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::config::{KeyCode, KeyEvent, KeyModifier};
+
+use super::help_menu::centered_rect;
+
+/// The state of a `ConfirmDialog` after handling a key event.
+pub enum ConfirmState {
+    /// The user hasn't answered yet.
+    Pending,
+    /// The user has answered yes.
+    Confirmed,
+    /// The user has answered no.
+    Declined,
+}
+
+/// A popup asking the user a yes/no question, which gates the execution of
+/// all following operations.
+pub struct ConfirmDialog {
+    message: String,
+}
+
+impl ConfirmDialog {
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    /// Answer the dialog according to a key event. All keys other than yes
+    /// and no are ignored.
+    pub fn handle_key(&self, key: &KeyEvent) -> ConfirmState {
+        match (key.modifier(), key.code()) {
+            (KeyModifier::None, KeyCode::Char('y' | 'Y')) => ConfirmState::Confirmed,
+            (KeyModifier::None, KeyCode::Char('n' | 'N') | KeyCode::Esc) => ConfirmState::Declined,
+            _ => ConfirmState::Pending,
+        }
+    }
+
+    /// Render to frame.
+    pub fn render(&self, frame: &mut Frame) {
+        let popup_area = centered_rect(60, 40, frame.size());
+
+        let text = format!("{}\n\n[y]es / [n]o", self.message);
+        let paragraph = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title("confirm")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Yellow)),
+            )
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_key() {
+        let dialog = ConfirmDialog::new("Delete?".to_owned());
+        let handle = |key: &str| dialog.handle_key(&key.parse().unwrap());
+
+        assert!(matches!(handle("y"), ConfirmState::Confirmed));
+        assert!(matches!(handle("n"), ConfirmState::Declined));
+        assert!(matches!(handle("esc"), ConfirmState::Declined));
+        assert!(matches!(handle("x"), ConfirmState::Pending));
+        assert!(matches!(handle("enter"), ConfirmState::Pending));
+    }
+}
@@ -4,6 +4,8 @@ use std::collections::HashMap;
 
 use crate::config::Table;
 
+use super::SELECTED_LINES_ENV_VAR;
+
 pub use self::env_variable::EnvVariable;
 
 #[derive(Default, Debug, Clone)]
@@ -31,6 +33,58 @@ impl EnvVariables {
         self.0.remove(env_var);
     }
 
+    /// Replace all references `$ENV` and `${ENV}` to environment variables
+    /// in `text` with their values. References to environment variables that
+    /// are not set are kept as they are.
+    pub fn expand(&self, text: &str) -> String {
+        let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(dollar_index) = rest.find('$') {
+            expanded.push_str(&rest[..dollar_index]);
+            let after_dollar = &rest[dollar_index + 1..];
+
+            let (name, reference_len) = match after_dollar.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], end + 2),
+                    None => ("", 0),
+                },
+                None => {
+                    let end = after_dollar
+                        .find(|c| !is_name_char(c))
+                        .unwrap_or(after_dollar.len());
+                    (&after_dollar[..end], end)
+                }
+            };
+
+            let value = name
+                .parse::<EnvVariable>()
+                .ok()
+                .filter(|_| !name.is_empty())
+                .and_then(|env_var| self.0.get(&env_var));
+            match value {
+                Some(value) => expanded.push_str(value),
+                None => expanded.push_str(&rest[dollar_index..dollar_index + 1 + reference_len]),
+            }
+            rest = &after_dollar[reference_len..];
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Replace all references to environment variables in an argument of an
+    /// argv command, like `expand`. An argument that only references
+    /// `$lines` is replaced with one argument per line instead.
+    pub fn expand_argument(&self, argument: &str) -> Vec<String> {
+        if let ("$lines" | "${lines}", Some(lines)) =
+            (argument, self.0.get(&SELECTED_LINES_ENV_VAR))
+        {
+            return lines.lines().map(str::to_owned).collect();
+        }
+        vec![self.expand(argument)]
+    }
+
     pub fn display<U>(&self, display_width: U) -> String
     where
         usize: From<U>,
@@ -68,3 +122,31 @@ impl FromIterator<(EnvVariable, String)> for EnvVariables {
         EnvVariables(iter.into_iter().collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let env_variables: EnvVariables = [
+            ("lines".parse().unwrap(), "a\nb".to_owned()),
+            ("pwd".parse().unwrap(), "/tmp".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            env_variables.expand("Delete $lines in ${pwd}/?"),
+            "Delete a\nb in /tmp/?"
+        );
+        assert_eq!(
+            env_variables.expand("$unset ${unset} $ ${ $"),
+            "$unset ${unset} $ ${ $"
+        );
+
+        assert_eq!(env_variables.expand_argument("$lines"), ["a", "b"]);
+        assert_eq!(env_variables.expand_argument("${lines}"), ["a", "b"]);
+        assert_eq!(env_variables.expand_argument("-$lines"), ["-a\nb"]);
+    }
+}
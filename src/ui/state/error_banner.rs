@@ -0,0 +1,51 @@
+use anyhow::Error;
+use ratatui::{prelude::Rect, widgets::Clear, Frame};
+
+use super::error_message::ErrorMessage;
+
+/// A banner displaying the error of the failed watched command, e.g. its exit
+/// code and stderr, which is shown below its previous output until the
+/// watched command succeeds again.
+pub struct ErrorBanner {
+    message: ErrorMessage,
+}
+
+impl ErrorBanner {
+    pub fn new(error: &Error) -> Self {
+        Self {
+            message: ErrorMessage::new(error),
+        }
+    }
+
+    /// Get the height required to display the whole message, but at most a
+    /// third of the `available_height`, so that the output stays visible.
+    pub fn height(&self, available_height: u16) -> u16 {
+        let message_height = u16::try_from(self.message.line_count()).unwrap_or(u16::MAX);
+        message_height.saturating_add(2).min(available_height / 3)
+    }
+
+    /// Render to the `area` of the frame.
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            self.message
+                .widget("watched command failed (showing previous output)"),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_height() {
+        let error =
+            anyhow!("Process \"false\" exited with status code 1:\n--STDERR--\n\n----------");
+        let banner = ErrorBanner::new(&error);
+        assert_eq!(banner.height(60), 6);
+        assert_eq!(banner.height(12), 4);
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Error;
+use itertools::Itertools;
+use ratatui::{
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+/// The message of an error, which is shown by the error popup and the error
+/// banner.
+pub struct ErrorMessage {
+    message: String,
+}
+
+impl ErrorMessage {
+    pub fn new(error: &Error) -> Self {
+        // Display every error in the chain of causes on its own line, e.g.
+        // the executed operation first, followed by the exit code and stderr.
+        let message = error.chain().map(ToString::to_string).join("\n");
+        Self { message }
+    }
+
+    /// Get the number of lines of the message.
+    pub fn line_count(&self) -> usize {
+        self.message.lines().count()
+    }
+
+    /// Get the message as a red-bordered widget with the `title`.
+    pub fn widget<'a>(&'a self, title: &'a str) -> Paragraph<'a> {
+        Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Red)),
+            )
+            .wrap(Wrap { trim: false })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context, Result};
+
+    #[test]
+    fn test_message_contains_all_causes() {
+        let result: Result<()> = Err(anyhow!("Process \"false\" exited with status code 1"))
+            .context("Execution of blocking subcommand \"exec -- false\" failed");
+        let message = ErrorMessage::new(&result.unwrap_err());
+        assert_eq!(
+            message.message,
+            "Execution of blocking subcommand \"exec -- false\" failed\nProcess \"false\" exited with status code 1"
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Error;
+use ratatui::{widgets::Clear, Frame};
+
+use super::error_message::ErrorMessage;
+use super::help_menu::centered_rect;
+
+/// A popup displaying an error, e.g. of a failed subcommand, which is shown
+/// until dismissed by the user.
+pub struct ErrorPopup {
+    message: ErrorMessage,
+}
+
+impl ErrorPopup {
+    pub fn new(error: &Error) -> Self {
+        Self {
+            message: ErrorMessage::new(error),
+        }
+    }
+
+    /// Render to frame.
+    pub fn render(&self, frame: &mut Frame) {
+        let popup_area = centered_rect(80, 60, frame.size());
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(
+            self.message.widget("error (press any key to dismiss)"),
+            popup_area,
+        );
+    }
+}
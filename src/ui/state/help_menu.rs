@@ -1,37 +1,40 @@
 use ratatui::{
-    prelude::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    prelude::{Constraint, Direction, Layout, Rect},
     text::Text,
-    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
     Frame,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::config::KeybindingsPrintable;
+use crate::config::{KeybindingsPrintable, ModeName};
 
-use super::EnvVariables;
+use super::{scrollable_popup::ScrollablePopup, EnvVariables};
 
 pub struct HelpMenu {
     env_variables: Arc<Mutex<EnvVariables>>,
     /// A local/non-shared copy of the shared `env_variables`.
     env_variables_copy: EnvVariables,
     keybindings: KeybindingsPrintable,
-    vertical_scroll_index: usize,
-    vertical_scroll_state: ScrollbarState,
+    /// The keybindings of each user-defined mode, sorted by mode name.
+    mode_keybindings: Vec<(ModeName, KeybindingsPrintable)>,
+    popup: ScrollablePopup,
 }
 
 // TODO: scrollbar should be hidden if not necessary; currently it's always shown
 
 impl HelpMenu {
-    pub fn new(keybindings: KeybindingsPrintable, env_variables: Arc<Mutex<EnvVariables>>) -> Self {
+    pub fn new(
+        keybindings: KeybindingsPrintable,
+        mut mode_keybindings: Vec<(ModeName, KeybindingsPrintable)>,
+        env_variables: Arc<Mutex<EnvVariables>>,
+    ) -> Self {
+        mode_keybindings.sort_by(|(a, _), (b, _)| a.cmp(b));
         HelpMenu {
             env_variables,
             env_variables_copy: EnvVariables::default(),
             keybindings,
-            vertical_scroll_state: ScrollbarState::default(),
-            vertical_scroll_index: 0,
-            // vertical_scroll_state: ScrollbarState::default()
-            //     .content_length(keybindings_str.lines().count() as u16),
+            mode_keybindings,
+            popup: ScrollablePopup::default(),
         }
     }
 
@@ -41,41 +44,21 @@ impl HelpMenu {
         // Get the inner popup width, so take borders into account.
         let popup_width = popup_area.width - 2;
 
-        let rendered_text = format!(
+        let mut rendered_text = format!(
             "ENV VARIABLES:\n{}\nKEYBINDINGS:\n{}\n",
             self.env_variables_copy.display(popup_width),
             self.keybindings.display(popup_width)
         );
+        for (mode, keybindings) in &self.mode_keybindings {
+            rendered_text.push_str(&format!(
+                "\nKEYBINDINGS IN MODE {}:\n{}\n",
+                mode,
+                keybindings.display(popup_width)
+            ));
+        }
 
         let text: Text = rendered_text.into();
-
-        // Render the paragraph with the updated scroll state
-        let paragraph = Paragraph::new(text)
-            .block(Block::default().title("help").borders(Borders::ALL))
-            .alignment(Alignment::Left)
-            // scroll offset for each axis: (y, x)
-            .scroll((self.vertical_scroll_index as u16, 0));
-
-        // Render the scrollbar next to the paragraph
-        frame.render_widget(Clear, popup_area);
-        frame.render_widget(paragraph, popup_area);
-
-        frame.render_stateful_widget(
-            Scrollbar::default()
-                .orientation(ScrollbarOrientation::VerticalRight)
-                .begin_symbol(None)
-                .end_symbol(None),
-            popup_area.inner(&Margin {
-                vertical: 1,
-                horizontal: 0,
-            }),
-            &mut self.vertical_scroll_state,
-        );
-    }
-
-    fn update_vertical_scroll_index(&mut self, index: usize) {
-        self.vertical_scroll_index = index;
-        self.vertical_scroll_state = self.vertical_scroll_state.position(index);
+        self.popup.render(frame, popup_area, "help", text);
     }
 
     // Moving
@@ -83,11 +66,16 @@ impl HelpMenu {
     pub fn move_down(&mut self, steps: usize) {
         // TODO: The lines might be wrapped, so we might actually have more indexes than, and therefore don't know what the last index is
         // TODO: Ideally, we only need to scroll if help content doesn't fit onto screen. But we don't know what fits on the screen currently, because we don't know if text got wrapped to the next line
-        self.update_vertical_scroll_index(self.vertical_scroll_index.saturating_add(steps));
+        self.popup.move_down(steps);
     }
 
     pub fn move_up(&mut self, steps: usize) {
-        self.update_vertical_scroll_index(self.vertical_scroll_index.saturating_sub(steps));
+        self.popup.move_up(steps);
+    }
+
+    /// Get the number of text lines that fit into the help menu.
+    pub fn viewport_height(&self) -> usize {
+        self.popup.viewport_height()
     }
 
     pub fn move_to_first(&mut self) {
@@ -114,7 +102,7 @@ impl HelpMenu {
     }
 
     pub fn hide(&mut self) {
-        self.update_vertical_scroll_index(0);
+        self.popup.scroll_to(0);
     }
 }
 
@@ -0,0 +1,234 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use super::status_bar::format_duration;
+
+/// A bounded history of the past outputs of the watched command, which can
+/// be navigated to display an older output instead of the latest one.
+pub struct OutputHistory {
+    /// The past outputs, from oldest to newest.
+    snapshots: VecDeque<Snapshot>,
+    /// The maximum number of outputs that are kept, after which the oldest
+    /// output is dropped.
+    capacity: usize,
+    /// The index of the snapshot that is currently displayed, or `None` if
+    /// the latest output is displayed live.
+    position: Option<usize>,
+    /// Whether the latest snapshot is still being streamed, so that it is
+    /// replaced by the next streamed output.
+    streaming_latest: bool,
+}
+
+/// An output of the watched command.
+struct Snapshot {
+    output: String,
+    /// The time the output was received at.
+    time: Instant,
+}
+
+/// The position of the displayed snapshot in the history.
+#[derive(Clone, Copy)]
+pub struct HistoryPosition {
+    /// The 1-based position of the displayed snapshot.
+    position: usize,
+    /// The number of snapshots in the history.
+    len: usize,
+    /// The time the displayed snapshot was received at.
+    time: Instant,
+}
+
+impl OutputHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            position: None,
+            streaming_latest: false,
+        }
+    }
+
+    /// Add a new `output` to the history, dropping the oldest output if the
+    /// history is full. Returns the output that should be displayed now: the
+    /// new output if it is displayed live, or `None` if the displayed output
+    /// doesn't change.
+    pub fn push(&mut self, output: String) -> Option<&str> {
+        if self.capacity == 0 {
+            self.snapshots.clear();
+            self.snapshots.push_back(Snapshot::new(output));
+            return self.snapshots.back().map(Snapshot::output);
+        }
+
+        let mut dropped_displayed = false;
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+            if let Some(position) = &mut self.position {
+                dropped_displayed = *position == 0;
+                *position = position.saturating_sub(1);
+            }
+        }
+        self.snapshots.push_back(Snapshot::new(output));
+
+        match self.position {
+            None => self.snapshots.back().map(Snapshot::output),
+            // The displayed snapshot was dropped, so display the oldest one.
+            Some(position) if dropped_displayed => {
+                self.snapshots.get(position).map(Snapshot::output)
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// Replace the latest output with a streamed `output`, which contains the
+    /// previously streamed output, or add it as a new output if a new stream
+    /// has been started. Returns the output that should be displayed now, like
+    /// `push`.
+    pub fn push_streamed(&mut self, output: String) -> Option<&str> {
+        if !self.streaming_latest {
+            self.streaming_latest = true;
+            return self.push(output);
+        }
+
+        let latest = self.snapshots.back_mut()?;
+        *latest = Snapshot::new(output);
+        match self.position {
+            None => Some(latest.output()),
+            Some(_) => None,
+        }
+    }
+
+    /// Start a new stream, whose output is added as a new output instead of
+    /// replacing the latest one.
+    pub fn start_stream(&mut self) {
+        self.streaming_latest = false;
+    }
+
+    /// Whether the latest output is displayed live.
+    pub fn is_live(&self) -> bool {
+        self.position.is_none()
+    }
+
+    /// Move to the previous output. Returns the output that should be
+    /// displayed now, or `None` if there is no previous output.
+    pub fn prev(&mut self) -> Option<&str> {
+        let position = match self.position {
+            // The latest output is displayed live.
+            None => self.snapshots.len().checked_sub(2)?,
+            Some(position) => position.checked_sub(1)?,
+        };
+        self.position = Some(position);
+        self.snapshots.get(position).map(Snapshot::output)
+    }
+
+    /// Move to the next output, which resumes displaying the output live once
+    /// the latest output is reached. Returns the output that should be
+    /// displayed now, or `None` if the output is already displayed live.
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        self.position = (position + 1 < self.snapshots.len()).then_some(position);
+        self.snapshots.get(position).map(Snapshot::output)
+    }
+
+    /// Resume displaying the latest output live. Returns the output that
+    /// should be displayed now, or `None` if the output is already displayed
+    /// live.
+    pub fn live(&mut self) -> Option<&str> {
+        self.position.take()?;
+        self.snapshots.back().map(Snapshot::output)
+    }
+
+    /// Get the position of the displayed snapshot, or `None` if the latest
+    /// output is displayed live.
+    pub fn position(&self) -> Option<HistoryPosition> {
+        let position = self.position?;
+        let snapshot = self.snapshots.get(position)?;
+        Some(HistoryPosition {
+            position: position + 1,
+            len: self.snapshots.len(),
+            time: snapshot.time,
+        })
+    }
+}
+
+impl Snapshot {
+    fn new(output: String) -> Self {
+        Self {
+            output,
+            time: Instant::now(),
+        }
+    }
+
+    fn output(&self) -> &str {
+        &self.output
+    }
+}
+
+impl HistoryPosition {
+    /// Describe the position and the age of the displayed snapshot at the
+    /// point in time `now`.
+    pub fn describe(&self, now: Instant) -> String {
+        format!(
+            "history {}/{} ({} ago)",
+            self.position,
+            self.len,
+            format_duration(now.saturating_duration_since(self.time))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigate_history() {
+        let mut history = OutputHistory::new(3);
+        assert_eq!(history.push("a".to_owned()), Some("a"));
+        assert_eq!(history.prev(), None);
+        assert_eq!(history.push("b".to_owned()), Some("b"));
+        assert_eq!(history.push("c".to_owned()), Some("c"));
+
+        assert_eq!(history.prev(), Some("b"));
+        assert_eq!(history.prev(), Some("a"));
+        assert_eq!(history.prev(), None);
+        assert_eq!(
+            history.position().map(|p| (p.position, p.len)),
+            Some((1, 3))
+        );
+
+        // New outputs aren't displayed while viewing the history, unless the
+        // displayed snapshot is dropped.
+        assert_eq!(history.push("d".to_owned()), Some("b"));
+        assert_eq!(history.push("e".to_owned()), Some("c"));
+        assert_eq!(history.next(), Some("d"));
+        assert_eq!(history.next(), Some("e"));
+        assert!(history.position().is_none());
+        assert_eq!(history.next(), None);
+
+        assert_eq!(history.prev(), Some("d"));
+        assert_eq!(history.push("f".to_owned()), None);
+        assert_eq!(history.live(), Some("f"));
+        assert_eq!(history.live(), None);
+    }
+
+    #[test]
+    fn test_streamed_history() {
+        let mut history = OutputHistory::new(3);
+        assert_eq!(history.push_streamed("a".to_owned()), Some("a"));
+        assert_eq!(history.push_streamed("a\nb".to_owned()), Some("a\nb"));
+        assert_eq!(history.prev(), None);
+
+        history.start_stream();
+        assert_eq!(history.push_streamed("c".to_owned()), Some("c"));
+        assert_eq!(history.prev(), Some("a\nb"));
+        assert_eq!(history.push_streamed("c\nd".to_owned()), None);
+        assert_eq!(history.next(), Some("c\nd"));
+    }
+
+    #[test]
+    fn test_disabled_history() {
+        let mut history = OutputHistory::new(0);
+        assert_eq!(history.push("a".to_owned()), Some("a"));
+        assert_eq!(history.push("b".to_owned()), Some("b"));
+        assert_eq!(history.prev(), None);
+    }
+}
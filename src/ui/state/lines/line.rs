@@ -1,7 +1,11 @@
 use ansi_to_tui::IntoText;
 use anyhow::Result;
 use itertools::Itertools;
-use ratatui::{style::Style, text::Text, widgets::Cell};
+use ratatui::{
+    style::Style,
+    text::{Span, Text},
+    widgets::Cell,
+};
 
 pub struct Line {
     /// Unformatted string that has any ANSI escape codes stripped out.
@@ -13,14 +17,33 @@ pub struct Line {
     /// ANSI codes. Does not contain the user style. Immutable for the lifetime
     /// of the line.
     displayed_text: Text<'static>,
+    /// The user style (style settings that should apply to the whole line)
+    /// that is currently applied.
+    user_style: Style,
+    /// The substring, and its style, whose occurrences are currently
+    /// highlighted in the displayed text.
+    highlight: Option<Highlight>,
     /// A cell containing the `displayed_text`, but with any user styles (style
     /// settings that should apply to the whole line), provided at creation
     /// and/or later, applied. If there is overlap in a setting between the
     /// `displayed_text`s style and the user style, the user style is
-    /// prioritized.
+    /// prioritized. Highlighted substrings are styled on top of that.
     displayed: Cell<'static>,
 }
 
+/// All occurrences of `pattern` should be styled with `style`.
+#[derive(Clone)]
+pub struct Highlight {
+    pattern: String,
+    style: Style,
+}
+
+impl Highlight {
+    pub fn new(pattern: String, style: Style) -> Self {
+        Self { pattern, style }
+    }
+}
+
 impl<'a> Line {
     /// Create a new Line. Apply the `user_style` to the whole line.
     /// The formatted string was formatted according to the user's field
@@ -35,7 +58,7 @@ impl<'a> Line {
         let formatted_or_unformatted = formatted_ansi.as_ref().unwrap_or(&unformatted_ansi);
 
         let displayed_text = Self::format_line_content(formatted_or_unformatted).into_text()?;
-        let displayed = Self::build_displayed_style(&displayed_text, user_style);
+        let displayed = Self::build_displayed_style(&displayed_text, user_style, None);
 
         let unformatted = unformatted_ansi.into_text()?.to_unformatted_string();
 
@@ -43,6 +66,8 @@ impl<'a> Line {
             unformatted,
             displayed,
             displayed_text,
+            user_style,
+            highlight: None,
         })
     }
 
@@ -54,26 +79,56 @@ impl<'a> Line {
 
     /// Build the final style of the displayed cell, which consists of the
     /// displayed text's inherent style and the user style. If any style
-    /// settings overlap, the user style is taken.
-    fn build_displayed_style(displayed_text: &Text<'a>, user_style: Style) -> Cell<'a> {
+    /// settings overlap, the user style is taken. Any occurrences of the
+    /// `highlight` pattern are additionally styled with the highlight style.
+    fn build_displayed_style(
+        displayed_text: &Text<'a>,
+        user_style: Style,
+        highlight: Option<&Highlight>,
+    ) -> Cell<'a> {
         // We don't want to add the user style to the displayed text, so clone.
         let mut displayed_text = displayed_text.clone();
         // Merge the style from the displayed text and the user style, and
         // prioritise the user style.
         displayed_text.patch_style(user_style);
+        if let Some(highlight) = highlight {
+            displayed_text.highlight(highlight);
+        }
         // Also apply user style to whole cell, so areas outside the text but
         // still inside the cell are also styled.
         Cell::from(displayed_text).style(user_style)
     }
 
     /// Draw the line.
-    pub fn draw(&self) -> Cell {
+    pub fn draw(&self) -> Cell<'_> {
         self.displayed.clone()
     }
 
     /// Update the style of the whole line.
     pub fn update_style(&mut self, new_style: Style) {
-        self.displayed = Self::build_displayed_style(&self.displayed_text, new_style);
+        self.user_style = new_style;
+        self.rebuild_displayed();
+    }
+
+    /// Update the substring whose occurrences are highlighted, or remove the
+    /// highlighting if `None`.
+    pub fn update_highlight(&mut self, new_highlight: Option<Highlight>) {
+        self.highlight = new_highlight;
+        self.rebuild_displayed();
+    }
+
+    /// Rebuild the displayed cell from the current user style and highlight.
+    fn rebuild_displayed(&mut self) {
+        self.displayed = Self::build_displayed_style(
+            &self.displayed_text,
+            self.user_style,
+            self.highlight.as_ref(),
+        );
+    }
+
+    /// Returns whether the unformatted line contains `pattern`.
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.unformatted.contains(pattern)
     }
 
     /// Get the line as a &str.
@@ -87,6 +142,68 @@ impl<'a> Line {
     }
 }
 
+trait Highlightable {
+    /// Style all occurrences of the highlight pattern with the highlight
+    /// style, on top of any existing styles.
+    fn highlight(&mut self, highlight: &Highlight);
+}
+
+impl<'a> Highlightable for Text<'a> {
+    fn highlight(&mut self, highlight: &Highlight) {
+        if highlight.pattern.is_empty() {
+            return;
+        }
+
+        for line in self.lines.iter_mut() {
+            let content: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            let matches: Vec<(usize, usize)> = content
+                .match_indices(&highlight.pattern)
+                .map(|(start, pattern)| (start, start + pattern.len()))
+                .collect();
+            if matches.is_empty() {
+                continue;
+            }
+
+            // Split every span at the boundaries of the matches, and style
+            // the parts that lie inside a match.
+            let mut highlighted_spans = vec![];
+            let mut span_start = 0;
+            for span in line.spans.drain(..) {
+                let span_end = span_start + span.content.len();
+                let mut boundaries = vec![span_start, span_end];
+                for &(start, end) in &matches {
+                    boundaries.extend(
+                        [start, end]
+                            .into_iter()
+                            .filter(|&b| span_start < b && b < span_end),
+                    );
+                }
+                boundaries.sort_unstable();
+                boundaries.dedup();
+
+                for (&from, &to) in boundaries.iter().tuple_windows() {
+                    let part = span.content[from - span_start..to - span_start].to_owned();
+                    let is_match = matches
+                        .iter()
+                        .any(|&(start, end)| start <= from && to <= end);
+                    let style = if is_match {
+                        span.style.patch(highlight.style)
+                    } else {
+                        span.style
+                    };
+                    highlighted_spans.push(Span::styled(part, style));
+                }
+                span_start = span_end;
+            }
+            line.spans = highlighted_spans;
+        }
+    }
+}
+
 trait ToUnformattedString {
     /// Extract the unformatted string underlying a `Text` object.
     fn to_unformatted_string(&self) -> String;
@@ -105,3 +222,30 @@ impl<'a> ToUnformattedString for Text<'a> {
             .join("\n")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_highlight_across_spans() {
+        let highlight_style = Style::default().bg(Color::Yellow);
+        let mut text = Text::from(ratatui::text::Line::from(vec![
+            Span::raw("ab"),
+            Span::styled("cd", Style::default().fg(Color::Red)),
+        ]));
+        text.highlight(&Highlight::new("bc".to_owned(), highlight_style));
+
+        let spans = &text.lines[0].spans;
+        assert_eq!(
+            spans,
+            &vec![
+                Span::raw("a"),
+                Span::styled("b", highlight_style),
+                Span::styled("c", Style::default().fg(Color::Red).bg(Color::Yellow)),
+                Span::styled("d", Style::default().fg(Color::Red)),
+            ]
+        );
+    }
+}
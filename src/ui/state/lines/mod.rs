@@ -5,19 +5,22 @@ use anyhow::Result;
 use derive_more::{From, Into};
 use itertools::{izip, Itertools};
 use ratatui::{
-    prelude::Constraint,
+    prelude::{Constraint, Rect},
     style::Style,
     widgets::{Row, Table, TableState},
     Frame,
 };
-use std::cmp::max;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
 
 use crate::config::Styles;
-use crate::config::{Fields, TableFormatter};
+use crate::config::{Fields, LineIdentity, LinePattern, TableFormatter};
 
 use self::selected_lines::LineSelections;
 
-pub use self::line::Line;
+pub use self::line::{Highlight, Line};
 
 /// The state of the lines, which can be drawn in order to be displayed
 /// in the UI.
@@ -35,17 +38,53 @@ pub struct Lines {
     /// Specifies the delimiter and shown fields that should be displayed
     /// for each line.
     fields: Fields,
+    /// How selected lines are identified in new lines, to keep them selected.
+    selection_identity: LineIdentity,
+    /// How the line the cursor is on is identified in new lines, to keep the
+    /// cursor on it.
+    cursor_identity: LineIdentity,
+    /// Whether lines that changed since the previous update are highlighted.
+    highlight_changes: bool,
+    /// How long changed lines are highlighted for, or `None` if they are
+    /// highlighted until the next update.
+    highlight_changes_duration: Option<Duration>,
+    /// For each line, the time it changed at compared to the corresponding
+    /// line of a previous update, as long as it is highlighted as changed.
+    changed_at: Vec<Option<Instant>>,
+    /// Whether the lines have been updated before, since the first lines
+    /// have nothing to be compared to.
+    updated_before: bool,
     /// The first index after the header lines, which is the smallest possible
     /// index the cursor can take.
     index_after_header_lines: usize,
     /// The line index of the cursor.
     cursor_index: Option<usize>,
+    /// The pattern that is currently being searched for, if any.
+    search_pattern: Option<String>,
+    /// The pattern that lines must contain in order to be displayed, if any.
+    filter_pattern: Option<String>,
+    /// The indexes of all lines that are displayed, in ascending order. All
+    /// lines are displayed, except those hidden by the filter pattern. The
+    /// header lines are always displayed. The cursor can only be on a
+    /// displayed line. The position of an index in this vector is the row
+    /// the line is displayed in.
+    visible_indices: Vec<usize>,
+    /// The area of the terminal the lines were last rendered to.
+    area: Rect,
     // TODO: deprecate in future
     table_state: TableState,
 }
 
 impl Lines {
-    pub fn new(fields: Fields, styles: Styles, header_lines: usize) -> Self {
+    pub fn new(
+        fields: Fields,
+        styles: Styles,
+        header_lines: usize,
+        selection_identity: LineIdentity,
+        cursor_identity: LineIdentity,
+        highlight_changes: bool,
+        highlight_changes_duration: Option<Duration>,
+    ) -> Self {
         Self {
             lines: vec![],
             line_selections: LineSelections::new(
@@ -54,24 +93,40 @@ impl Lines {
                 header_lines,
             ),
             fields,
+            selection_identity,
+            cursor_identity,
+            highlight_changes,
+            highlight_changes_duration,
+            changed_at: vec![],
+            updated_before: false,
             cursor_index: None,
+            search_pattern: None,
+            filter_pattern: None,
+            visible_indices: vec![],
             styles,
             index_after_header_lines: header_lines,
+            area: Rect::default(),
             table_state: TableState::default(),
         }
     }
 
-    /// Render to frame.
-    pub fn render(&mut self, frame: &mut Frame) {
+    /// Render to the `area` of the frame.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.expire_changes();
+
         // TODO: do as much as possible in update_lines to improve performance
-        let rows: Vec<Row> = izip!(self.lines.iter(), self.line_selections.iter())
+        let rows: Vec<Row> = self
+            .visible_indices
+            .iter()
+            .filter_map(|&i| Some((self.lines.get(i)?, self.line_selections.get(i)?)))
             .map(|(line, selected)| Row::new(vec![selected.draw(), line.draw()]))
             .collect();
 
         let widths = [Constraint::Length(1), Constraint::Percentage(100)];
         let table = Table::new(rows, widths).column_spacing(0);
 
-        frame.render_stateful_widget(table, frame.size(), &mut self.table_state);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+        self.area = area;
     }
 
     /// Update the lines to `new_lines`.
@@ -84,7 +139,7 @@ impl Lines {
                 None => vec![None; new_lines.lines().count()],
             };
 
-        self.lines = izip!(new_lines.lines(), formatted)
+        let lines = izip!(new_lines.lines(), formatted)
             .enumerate()
             .map(|(i, (unformatted, formatted))| {
                 let style = if i < self.index_after_header_lines {
@@ -94,65 +149,261 @@ impl Lines {
                 };
                 Line::new(unformatted.to_owned(), formatted, style)
             })
-            .collect::<Result<_>>()?;
+            .collect::<Result<Vec<Line>>>()?;
+        let old_cursor_row = self.get_cursor_row();
+        let old_lines = std::mem::replace(&mut self.lines, lines);
 
-        // Resize the line selections to the same size as the lines.
-        self.line_selections.resize(self.lines.len());
+        self.update_changes(&old_lines);
 
-        self.calibrate_cursor();
+        match self.selection_identity {
+            // Resize the line selections to the same size as the lines.
+            LineIdentity::Index => self.line_selections.resize(self.lines.len()),
+            // Move the line selections to the new lines with the same key.
+            identity => {
+                let header_lines = self.index_after_header_lines;
+                let old_keys = line_keys(&old_lines, identity, &self.fields, header_lines);
+                let new_keys = line_keys(&self.lines, identity, &self.fields, header_lines);
+                self.line_selections.remap(&old_keys, &new_keys);
+            }
+        }
+
+        if self.search_pattern.is_some() {
+            self.update_search_highlights();
+        }
+
+        self.update_visible_indices();
+
+        // Keep the cursor on the same line, or calibrate it if that line
+        // disappeared.
+        let old_cursor_key = self
+            .cursor_index
+            .and_then(|i| old_lines.get(i))
+            .and_then(|line| {
+                self.cursor_identity
+                    .key(line.unformatted_str(), &self.fields)
+            });
+        let new_cursor_row = old_cursor_key
+            .zip(old_cursor_row)
+            .and_then(|(key, old_row)| self.find_row_of_key(key, old_row));
+        match new_cursor_row {
+            Some(row) => self.move_cursor(row as isize),
+            None => self.calibrate_cursor(),
+        }
 
         Ok(())
     }
+
+    /// Mark the lines that differ from their corresponding `old_lines` as
+    /// changed, and highlight them. Lines correspond to each other according
+    /// to the selection identity, so that lines that were only shifted by
+    /// inserted or deleted lines aren't highlighted, unless lines are
+    /// identified by their index.
+    fn update_changes(&mut self, old_lines: &[Line]) {
+        let now = Instant::now();
+        let compare = self.highlight_changes && self.updated_before;
+        self.updated_before = true;
+
+        let old_indices = self.corresponding_old_indices(old_lines);
+        self.changed_at = izip!(&self.lines, old_indices)
+            .enumerate()
+            .map(|(i, (line, old_index))| {
+                let changed = compare
+                    && i >= self.index_after_header_lines
+                    && old_index.map(|j| old_lines[j].unformatted_str())
+                        != Some(line.unformatted_str());
+                if changed {
+                    Some(now)
+                } else if self.highlight_changes_duration.is_some() {
+                    // Keep highlighting the line until the duration expires.
+                    old_index.and_then(|j| self.changed_at.get(j).copied().flatten())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        for i in 0..self.lines.len() {
+            if self.changed_at[i].is_some() {
+                self.update_line_style(i, self.styles.changed);
+            }
+        }
+    }
+
+    /// Get the index of the old line each line corresponds to according to the
+    /// selection identity, or `None` if there is none. If there are several
+    /// lines with the same key, they correspond to each other in order.
+    fn corresponding_old_indices(&self, old_lines: &[Line]) -> Vec<Option<usize>> {
+        let identity = self.selection_identity;
+        if let LineIdentity::Index = identity {
+            return (0..self.lines.len())
+                .map(|i| (i < old_lines.len()).then_some(i))
+                .collect();
+        }
+
+        let header_lines = self.index_after_header_lines;
+        let mut old_indices_by_key: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (j, key) in line_keys(old_lines, identity, &self.fields, header_lines)
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(key) = key {
+                old_indices_by_key.entry(key).or_default().push_back(j);
+            }
+        }
+
+        line_keys(&self.lines, identity, &self.fields, header_lines)
+            .into_iter()
+            .map(|key| {
+                key.and_then(|key| old_indices_by_key.get_mut(key))
+                    .and_then(VecDeque::pop_front)
+            })
+            .collect()
+    }
+
+    /// Stop highlighting the changed lines whose highlight duration expired.
+    fn expire_changes(&mut self) {
+        let Some(duration) = self.highlight_changes_duration else {
+            return;
+        };
+        let now = Instant::now();
+        for i in 0..self.changed_at.len() {
+            if self.changed_at[i].is_some_and(|changed_at| changed_at + duration <= now) {
+                self.changed_at[i] = None;
+                if self.cursor_index != Some(i) {
+                    self.update_line_style(i, self.styles.non_cursor_non_header);
+                }
+            }
+        }
+    }
+
+    /// Get the time until the next highlight of a changed line expires, or
+    /// `None` if no highlight expires.
+    pub fn next_change_expiry(&self) -> Option<Duration> {
+        let duration = self.highlight_changes_duration?;
+        let now = Instant::now();
+        self.changed_at
+            .iter()
+            .flatten()
+            .min()
+            .map(|&changed_at| (changed_at + duration).saturating_duration_since(now))
+    }
+
+    /// Get the style of the non-header line at `index`, if the cursor is not
+    /// on it.
+    fn non_cursor_style(&self, index: usize) -> Style {
+        match self.changed_at.get(index) {
+            Some(Some(_)) => self.styles.changed,
+            _ => self.styles.non_cursor_non_header,
+        }
+    }
+
+    /// Find the displayed row of the non-header line identified by `key`
+    /// according to the cursor identity. If there are several such lines, the
+    /// row nearest to `near_row` is chosen.
+    fn find_row_of_key(&self, key: &str, near_row: usize) -> Option<usize> {
+        self.visible_indices
+            .iter()
+            .enumerate()
+            .skip(self.index_after_header_lines)
+            .filter(|(_, &i)| {
+                self.cursor_identity
+                    .key(self.lines[i].unformatted_str(), &self.fields)
+                    == Some(key)
+            })
+            .map(|(row, _)| row)
+            .min_by_key(|row| row.abs_diff(near_row))
+    }
 }
 
 // Moving cursor
 impl Lines {
     // TODO: don't use isize, instead use an enum Up|Down and saturating_{add,sub}
 
-    /// Move the cursor to `index`.
-    fn move_cursor(&mut self, index: isize) {
+    /// Move the cursor to the displayed row at `position`.
+    fn move_cursor(&mut self, position: isize) {
         let old_cursor_index = self.get_cursor_position();
-        let new_cursor_index = if self.lines.is_empty() {
+        let first = self.index_after_header_lines;
+        let new_cursor_position = if self.visible_indices.len() <= first {
+            // There are no displayed lines except for header lines.
             None
         } else {
-            let first = self.index_after_header_lines as isize;
-            let last = self.last_index() as isize;
-            Some(index.clamp(first, last) as usize)
+            let last = self.visible_indices.len() - 1;
+            Some(position.clamp(first as isize, last as isize) as usize)
         };
+        let new_cursor_index = new_cursor_position.map(|pos| self.visible_indices[pos]);
 
         self.cursor_index = new_cursor_index;
-        self.table_state.select(self.cursor_index);
+        self.table_state.select(new_cursor_position);
         self.adjust_cursor_style(old_cursor_index, new_cursor_index);
+        self.update_range_selection();
     }
 
     /// Get the current cursor index, or `None` if there is currently no cursor.
-    fn get_cursor_position(&self) -> Option<usize> {
+    pub fn get_cursor_position(&self) -> Option<usize> {
+        self.cursor_index
+    }
+
+    /// Move the cursor back to the line at `index`, e.g. one returned by
+    /// `get_cursor_position`, or to the next displayed line if it is not
+    /// displayed.
+    pub fn move_cursor_to_position(&mut self, index: usize) {
+        let row = self
+            .visible_indices
+            .partition_point(|&visible| visible < index);
+        self.move_cursor(row as isize);
+    }
+
+    /// Get the displayed row of the cursor, or `None` if there is currently
+    /// no cursor.
+    fn get_cursor_row(&self) -> Option<usize> {
         self.cursor_index
+            .map(|i| self.visible_indices.partition_point(|&visible| visible < i))
     }
 
     /// Calibrate the cursor. Calibration may be necessary if the cursor is
-    /// still on a line that no longer exists.
+    /// still on a line that no longer exists or is no longer displayed. In
+    /// that case, the cursor is moved to the next displayed line.
     fn calibrate_cursor(&mut self) {
-        match self.get_cursor_position() {
+        match self.get_cursor_row() {
             None => self.move_cursor_to_first_line(),
-            Some(i) => self.move_cursor(i as isize),
+            Some(row) => self.move_cursor(row as isize),
         };
     }
 
     /// Move the cursor down by `steps`.
     pub fn move_cursor_down(&mut self, steps: usize) {
-        if let Some(i) = self.get_cursor_position() {
-            self.move_cursor(i as isize + steps as isize);
+        if let Some(row) = self.get_cursor_row() {
+            self.move_cursor(row as isize + steps as isize);
         }
     }
 
     /// Move the cursor up by `steps`.
     pub fn move_cursor_up(&mut self, steps: usize) {
-        if let Some(i) = self.get_cursor_position() {
-            self.move_cursor(i as isize - steps as isize);
+        if let Some(row) = self.get_cursor_row() {
+            self.move_cursor(row as isize - steps as isize);
         }
     }
 
+    /// Get the 1-based position of the cursor among the displayed non-header
+    /// lines, together with the number of displayed non-header lines, or
+    /// `None` if there is currently no cursor.
+    pub fn cursor_position_of_total(&self) -> Option<(usize, usize)> {
+        let first = self.index_after_header_lines;
+        self.get_cursor_row().map(|row| {
+            (
+                row + 1 - first,
+                self.visible_indices.len().saturating_sub(first),
+            )
+        })
+    }
+
+    /// Returns whether the cursor is on the last displayed line, or there is
+    /// no cursor.
+    pub fn is_cursor_on_last_line(&self) -> bool {
+        self.get_cursor_row()
+            .is_none_or(|row| row + 1 >= self.visible_indices.len())
+    }
+
     /// Move the cursor to the first line.
     pub fn move_cursor_to_first_line(&mut self) {
         self.move_cursor(self.index_after_header_lines as isize);
@@ -160,7 +411,91 @@ impl Lines {
 
     /// Move the cursor to the last line.
     pub fn move_cursor_to_last_line(&mut self) {
-        self.move_cursor(self.last_index() as isize);
+        self.move_cursor(self.visible_indices.len() as isize - 1);
+    }
+
+    /// Get the number of rows that fit into the area the lines were last
+    /// rendered to.
+    fn viewport_height(&self) -> usize {
+        usize::from(self.area.height)
+    }
+
+    /// Scroll the viewport down by `rows` (or up, if negative), and move the
+    /// cursor along with it, so that the cursor keeps its position in the
+    /// viewport, if possible.
+    fn scroll(&mut self, rows: isize) {
+        if let Some(cursor_row) = self.get_cursor_row() {
+            let max_offset = self
+                .visible_indices
+                .len()
+                .saturating_sub(self.viewport_height());
+            let offset = (self.table_state.offset() as isize + rows).clamp(0, max_offset as isize);
+            *self.table_state.offset_mut() = offset as usize;
+            self.move_cursor(cursor_row as isize + rows);
+        }
+    }
+
+    /// Scroll down by the height of the viewport.
+    pub fn move_cursor_page_down(&mut self) {
+        self.scroll(self.viewport_height().max(1) as isize);
+    }
+
+    /// Scroll up by the height of the viewport.
+    pub fn move_cursor_page_up(&mut self) {
+        self.scroll(-(self.viewport_height().max(1) as isize));
+    }
+
+    /// Scroll down by half the height of the viewport.
+    pub fn move_cursor_half_page_down(&mut self) {
+        self.scroll((self.viewport_height() / 2).max(1) as isize);
+    }
+
+    /// Scroll up by half the height of the viewport.
+    pub fn move_cursor_half_page_up(&mut self) {
+        self.scroll(-((self.viewport_height() / 2).max(1) as isize));
+    }
+
+    /// Get the first and last row of the non-header lines that are currently
+    /// displayed in the viewport, or `None` if there are none.
+    fn viewport_rows(&self) -> Option<(usize, usize)> {
+        let offset = self.table_state.offset();
+        let top = offset.max(self.index_after_header_lines);
+        let bottom = (offset + self.viewport_height()).min(self.visible_indices.len());
+        (top < bottom).then(|| (top, bottom - 1))
+    }
+
+    /// Move the cursor to the first line displayed in the viewport.
+    pub fn move_cursor_to_screen_top(&mut self) {
+        if let Some((top, _)) = self.viewport_rows() {
+            self.move_cursor(top as isize);
+        }
+    }
+
+    /// Move the cursor to the line in the middle of the viewport.
+    pub fn move_cursor_to_screen_middle(&mut self) {
+        if let Some((top, bottom)) = self.viewport_rows() {
+            self.move_cursor(((top + bottom) / 2) as isize);
+        }
+    }
+
+    /// Move the cursor to the last line displayed in the viewport.
+    pub fn move_cursor_to_screen_bottom(&mut self) {
+        if let Some((_, bottom)) = self.viewport_rows() {
+            self.move_cursor(bottom as isize);
+        }
+    }
+
+    /// Move the cursor to the line displayed at the terminal `screen_row`.
+    /// The cursor is not moved if there is no non-header line displayed at
+    /// that row.
+    pub fn move_cursor_to_screen_row(&mut self, screen_row: u16) {
+        if !(self.area.top()..self.area.bottom()).contains(&screen_row) {
+            return;
+        }
+        let row = self.table_state.offset() + usize::from(screen_row - self.area.top());
+        if (self.index_after_header_lines..self.visible_indices.len()).contains(&row) {
+            self.move_cursor(row as isize);
+        }
     }
 }
 
@@ -177,7 +512,7 @@ impl Lines {
         new_cursor_index: Option<usize>,
     ) {
         if let Some(old_index) = old_cursor_index {
-            self.update_line_style(old_index, self.styles.non_cursor_non_header);
+            self.update_line_style(old_index, self.non_cursor_style(old_index));
         }
         if let Some(new_index) = new_cursor_index {
             self.update_line_style(new_index, self.styles.cursor);
@@ -196,6 +531,7 @@ impl Lines {
 impl Lines {
     /// Select the line that the cursor is currently on.
     pub fn select_current(&mut self) {
+        self.line_selections.end_range();
         if let Some(i) = self.get_cursor_position() {
             self.line_selections.select_at_index(i);
         }
@@ -203,6 +539,7 @@ impl Lines {
 
     /// Unselect the line that the cursor is currently on.
     pub fn unselect_current(&mut self) {
+        self.line_selections.end_range();
         if let Some(i) = self.get_cursor_position() {
             self.line_selections.unselect_at_index(i);
         }
@@ -210,20 +547,248 @@ impl Lines {
 
     /// Toggle the selection of the line that the cursor is currently on.
     pub fn toggle_selection_current(&mut self) {
+        self.line_selections.end_range();
         if let Some(i) = self.get_cursor_position() {
             self.line_selections.toggle_selection_at_index(i);
         }
     }
 
-    /// Select all lines.
+    /// Select all lines. If lines are being filtered, only select the
+    /// displayed lines.
     pub fn select_all(&mut self) {
-        self.line_selections.select_all();
+        self.line_selections.end_range();
+        if self.is_filtered() {
+            for &i in self
+                .visible_indices
+                .iter()
+                .skip(self.index_after_header_lines)
+            {
+                self.line_selections.select_at_index(i);
+            }
+        } else {
+            self.line_selections.select_all();
+        }
     }
 
     /// Unselect all lines.
     pub fn unselect_all(&mut self) {
+        self.line_selections.end_range();
         self.line_selections.unselect_all();
     }
+
+    /// Get the indexes of all displayed non-header lines.
+    fn selectable_indices(&self) -> Vec<usize> {
+        self.visible_indices
+            .iter()
+            .skip(self.index_after_header_lines)
+            .copied()
+            .collect()
+    }
+
+    /// Get the indexes of all displayed non-header lines matching `pattern`.
+    fn matching_indices(&self, pattern: &LinePattern) -> Vec<usize> {
+        self.selectable_indices()
+            .into_iter()
+            .filter(|&i| pattern.is_match(self.lines[i].unformatted_str(), &self.fields))
+            .collect()
+    }
+
+    /// Select all displayed lines matching the `pattern`.
+    pub fn select_matching(&mut self, pattern: &LinePattern) {
+        self.line_selections.end_range();
+        for i in self.matching_indices(pattern) {
+            self.line_selections.select_at_index(i);
+        }
+    }
+
+    /// Unselect all displayed lines matching the `pattern`.
+    pub fn unselect_matching(&mut self, pattern: &LinePattern) {
+        self.line_selections.end_range();
+        for i in self.matching_indices(pattern) {
+            self.line_selections.unselect_at_index(i);
+        }
+    }
+
+    /// Toggle the selection of all displayed lines.
+    pub fn invert_selection(&mut self) {
+        self.line_selections.end_range();
+        for i in self.selectable_indices() {
+            self.line_selections.toggle_selection_at_index(i);
+        }
+    }
+
+    /// Start selecting all lines from the line the cursor is currently on to
+    /// wherever the cursor is moved, until the range selection is ended.
+    pub fn start_range_selection(&mut self) {
+        if let Some(i) = self.get_cursor_position() {
+            self.line_selections.start_range(i);
+            self.update_range_selection();
+        }
+    }
+
+    /// End the range selection, keeping the lines in the range selected.
+    pub fn end_range_selection(&mut self) {
+        self.line_selections.end_range();
+    }
+
+    /// Start a range selection, or end it if one is already in progress.
+    pub fn toggle_range_selection(&mut self) {
+        match self.line_selections.range_anchor() {
+            Some(_) => self.end_range_selection(),
+            None => self.start_range_selection(),
+        }
+    }
+
+    /// Select all displayed lines between the anchor of the range selection
+    /// and the cursor, if a range selection is in progress.
+    fn update_range_selection(&mut self) {
+        let (Some(anchor), Some(cursor_row)) =
+            (self.line_selections.range_anchor(), self.get_cursor_row())
+        else {
+            return;
+        };
+        // The anchor line might not be displayed anymore, in which case the
+        // range starts at the next displayed line.
+        let anchor_row = self.visible_indices.partition_point(|&i| i < anchor).clamp(
+            self.index_after_header_lines,
+            self.visible_indices.len() - 1,
+        );
+
+        let rows = anchor_row.min(cursor_row)..=anchor_row.max(cursor_row);
+        let indices = self.visible_indices[rows].to_vec();
+        self.line_selections.select_range(indices);
+    }
+
+    /// Get the number of selected lines that are displayed.
+    pub fn selected_count(&self) -> usize {
+        self.visible_selected_indices().count()
+    }
+
+    /// Iterate over the indices of all selected lines that are displayed.
+    /// Selected lines hidden by the filter are ignored, so that operations
+    /// never act on lines the user can't see.
+    fn visible_selected_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.visible_indices.iter().copied().filter(|&i| {
+            self.line_selections
+                .get(i)
+                .is_some_and(|selection| selection.is_selected())
+        })
+    }
+}
+
+/// The direction in which to search for the next matching line.
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+// Searching lines
+impl Lines {
+    /// Highlight all occurrences of `pattern` in all lines, or remove all
+    /// highlighting if `None`.
+    pub fn set_search_pattern(&mut self, pattern: Option<String>) {
+        // Searching for an empty pattern is equivalent to not searching.
+        self.search_pattern = pattern.filter(|pattern| !pattern.is_empty());
+        self.update_search_highlights();
+    }
+
+    /// Update the highlighting of all lines to the current search pattern.
+    fn update_search_highlights(&mut self) {
+        let highlight = self
+            .search_pattern
+            .clone()
+            .map(|pattern| Highlight::new(pattern, self.styles.search_match));
+        for line in self.lines.iter_mut().skip(self.index_after_header_lines) {
+            line.update_highlight(highlight.clone());
+        }
+    }
+
+    /// Move the cursor to the next displayed line, in the given `direction`,
+    /// that contains the search pattern. The search wraps around at the first
+    /// and last line. If `include_cursor_line` is set, the line the cursor is
+    /// currently on is considered first. Nothing happens if no line matches.
+    pub fn move_cursor_to_match(&mut self, direction: SearchDirection, include_cursor_line: bool) {
+        let (Some(pattern), Some(cursor_row)) = (&self.search_pattern, self.get_cursor_row())
+        else {
+            return;
+        };
+
+        let first = self.index_after_header_lines;
+        let searchable_len = self.visible_indices.len().saturating_sub(first);
+        if searchable_len == 0 {
+            return;
+        }
+
+        // Row of the cursor relative to the first non-header row.
+        let cursor_offset = cursor_row - first;
+        let start = if include_cursor_line { 0 } else { 1 };
+        let matching_row = (start..searchable_len + start)
+            .map(|step| match direction {
+                SearchDirection::Forward => (cursor_offset + step) % searchable_len,
+                SearchDirection::Backward => {
+                    (cursor_offset + searchable_len - step % searchable_len) % searchable_len
+                }
+            })
+            .map(|offset| first + offset)
+            .find(|&row| self.lines[self.visible_indices[row]].contains(pattern));
+
+        if let Some(row) = matching_row {
+            self.move_cursor(row as isize);
+        }
+    }
+}
+
+// Filtering lines
+impl Lines {
+    /// Only display lines that contain `pattern`, or display all lines if
+    /// `None`.
+    pub fn set_filter_pattern(&mut self, pattern: Option<String>) {
+        // Filtering for an empty pattern is equivalent to not filtering.
+        self.filter_pattern = pattern.filter(|pattern| !pattern.is_empty());
+        self.update_visible_indices();
+        self.calibrate_cursor();
+    }
+
+    /// Recompute which lines are displayed according to the filter pattern.
+    fn update_visible_indices(&mut self) {
+        let header_lines = self.index_after_header_lines.min(self.lines.len());
+        self.visible_indices = match &self.filter_pattern {
+            Some(pattern) => (0..header_lines)
+                .chain(
+                    (header_lines..self.lines.len()).filter(|&i| self.lines[i].contains(pattern)),
+                )
+                .collect(),
+            None => (0..self.lines.len()).collect(),
+        };
+    }
+
+    /// Returns whether lines are currently being filtered.
+    fn is_filtered(&self) -> bool {
+        self.filter_pattern.is_some()
+    }
+
+    /// Get the pattern that lines are currently filtered by, if any.
+    pub fn filter_pattern(&self) -> Option<&str> {
+        self.filter_pattern.as_deref()
+    }
+}
+
+/// Get the keys identifying the `lines`, where header lines have no key.
+fn line_keys<'a>(
+    lines: &'a [Line],
+    identity: LineIdentity,
+    fields: &Fields,
+    header_lines: usize,
+) -> Vec<Option<&'a str>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            (i >= header_lines)
+                .then(|| identity.key(line.unformatted_str(), fields))
+                .flatten()
+        })
+        .collect()
 }
 
 /// String content of the line on which the cursor is currently on.
@@ -241,10 +806,9 @@ impl Lines {
     /// the cursor line and the selected lines.
     pub fn get_cursor_line_and_selected_lines(&self) -> Option<(CursorLine, SelectedLines)> {
         self.get_line_under_cursor().map(|cursor_line| {
-            let mut selected_lines_iter = izip!(self.lines.iter(), self.line_selections.iter())
-                .filter_map(|(line, selection)| {
-                    selection.is_selected().then(|| line.unformatted_str())
-                })
+            let mut selected_lines_iter = self
+                .visible_selected_indices()
+                .map(|i| self.lines[i].unformatted_str())
                 .peekable();
 
             let selected_lines = match selected_lines_iter.peek() {
@@ -273,14 +837,387 @@ impl Lines {
     pub fn get_unformatted_line(&self, index: usize) -> Option<String> {
         self.lines.get(index).map(Line::unformatted_string)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_with_content(content: &str, header_lines: usize) -> Lines {
+        lines_with_identity(
+            content,
+            header_lines,
+            LineIdentity::Index,
+            LineIdentity::Index,
+        )
+    }
 
-    /// Get the index of the last line. The returned index will never be within
-    /// the header lines.
-    fn last_index(&self) -> usize {
-        if self.lines.is_empty() {
-            self.index_after_header_lines
-        } else {
-            max(self.index_after_header_lines, self.lines.len() - 1)
+    fn lines_with_identity(
+        content: &str,
+        header_lines: usize,
+        selection_identity: LineIdentity,
+        cursor_identity: LineIdentity,
+    ) -> Lines {
+        let styles = Styles {
+            cursor: Style::default(),
+            header: Style::default(),
+            non_cursor_non_header: Style::default(),
+            selected: Style::default(),
+            search_match: Style::default(),
+            changed: Style::default(),
+        };
+        let fields = Fields::try_new(None, None).unwrap();
+        let mut lines = Lines::new(
+            fields,
+            styles,
+            header_lines,
+            selection_identity,
+            cursor_identity,
+            false,
+            None,
+        );
+        lines.update_lines(content.to_owned()).unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_search_wraps_around() {
+        let mut lines = lines_with_content("header\nfoo\nbar\nfoo\nbaz", 1);
+        lines.set_search_pattern(Some("foo".to_owned()));
+        assert_eq!(lines.get_cursor_position(), Some(1));
+
+        lines.move_cursor_to_match(SearchDirection::Forward, false);
+        assert_eq!(lines.get_cursor_position(), Some(3));
+        lines.move_cursor_to_match(SearchDirection::Forward, false);
+        assert_eq!(lines.get_cursor_position(), Some(1));
+        lines.move_cursor_to_match(SearchDirection::Backward, false);
+        assert_eq!(lines.get_cursor_position(), Some(3));
+    }
+
+    #[test]
+    fn test_move_cursor_to_position_restores_cursor() {
+        let mut lines = lines_with_content("header\nfoo\nbar\nbaz", 1);
+        lines.move_cursor_down(1);
+        let position = lines.get_cursor_position().unwrap();
+
+        lines.set_search_pattern(Some("baz".to_owned()));
+        lines.move_cursor_to_match(SearchDirection::Forward, true);
+        assert_eq!(lines.get_cursor_position(), Some(3));
+
+        lines.move_cursor_to_position(position);
+        assert_eq!(lines.get_line_under_cursor(), Some("bar".to_owned()));
+    }
+
+    #[test]
+    fn test_search_ignores_header_lines() {
+        let mut lines = lines_with_content("foo\nbar\nbaz", 1);
+        lines.set_search_pattern(Some("foo".to_owned()));
+        lines.move_cursor_to_match(SearchDirection::Forward, true);
+        assert_eq!(lines.get_cursor_position(), Some(1));
+    }
+
+    #[test]
+    fn test_filter_maps_to_unfiltered_lines() {
+        let mut lines = lines_with_content("header\nfoo\nbar\nfoo2\nbaz", 1);
+        lines.set_filter_pattern(Some("foo".to_owned()));
+        assert_eq!(lines.visible_indices, vec![0, 1, 3]);
+
+        lines.move_cursor_down(1);
+        assert_eq!(lines.get_cursor_position(), Some(3));
+        lines.select_current();
+
+        let (cursor_line, selected_lines) = lines.get_cursor_line_and_selected_lines().unwrap();
+        assert_eq!(String::from(cursor_line), "foo2");
+        assert_eq!(String::from(selected_lines), "foo2");
+    }
+
+    #[test]
+    fn test_selected_lines_hidden_by_filter_are_ignored() {
+        let mut lines = lines_with_content("header\nfoo\nbar\nfoo2", 1);
+        lines.select_all();
+        lines.set_filter_pattern(Some("foo".to_owned()));
+        assert_eq!(lines.selected_count(), 2);
+
+        let (_, selected_lines) = lines.get_cursor_line_and_selected_lines().unwrap();
+        assert_eq!(String::from(selected_lines), "foo\nfoo2");
+
+        // Removing the filter shows the hidden selected line again.
+        lines.set_filter_pattern(None);
+        let (_, selected_lines) = lines.get_cursor_line_and_selected_lines().unwrap();
+        assert_eq!(String::from(selected_lines), "foo\nbar\nfoo2");
+    }
+
+    #[test]
+    fn test_move_cursor_to_screen_row() {
+        let mut lines = lines_with_content("header\nfoo\nbar\nbaz", 1);
+        lines.area = Rect::new(0, 2, 80, 10);
+
+        lines.move_cursor_to_screen_row(4);
+        assert_eq!(lines.get_line_under_cursor(), Some("bar".to_owned()));
+
+        // Clicking on the header, below the last line or outside of the area
+        // doesn't move the cursor.
+        for screen_row in [2, 6, 1, 12] {
+            lines.move_cursor_to_screen_row(screen_row);
+            assert_eq!(lines.get_line_under_cursor(), Some("bar".to_owned()));
         }
     }
+
+    #[test]
+    fn test_move_cursor_by_pages() {
+        let content = (0..20).map(|i| i.to_string()).join("\n");
+        let mut lines = lines_with_content(&format!("header\n{}", content), 1);
+        lines.area = Rect::new(0, 0, 80, 5);
+
+        lines.move_cursor_page_down();
+        assert_eq!(lines.get_line_under_cursor(), Some("5".to_owned()));
+        assert_eq!(lines.table_state.offset(), 5);
+
+        lines.move_cursor_half_page_up();
+        assert_eq!(lines.get_line_under_cursor(), Some("3".to_owned()));
+        assert_eq!(lines.table_state.offset(), 3);
+
+        lines.move_cursor_to_screen_bottom();
+        assert_eq!(lines.get_line_under_cursor(), Some("6".to_owned()));
+        lines.move_cursor_to_screen_middle();
+        assert_eq!(lines.get_line_under_cursor(), Some("4".to_owned()));
+        lines.move_cursor_to_screen_top();
+        assert_eq!(lines.get_line_under_cursor(), Some("2".to_owned()));
+
+        // The viewport can't be scrolled past the last line.
+        lines.move_cursor_page_down();
+        lines.move_cursor_page_down();
+        lines.move_cursor_page_down();
+        lines.move_cursor_page_down();
+        assert_eq!(lines.get_line_under_cursor(), Some("19".to_owned()));
+        assert_eq!(lines.table_state.offset(), 16);
+
+        // The header lines are never the cursor line.
+        lines.move_cursor_page_up();
+        lines.move_cursor_page_up();
+        lines.move_cursor_page_up();
+        lines.move_cursor_page_up();
+        lines.move_cursor_to_screen_top();
+        assert_eq!(lines.get_line_under_cursor(), Some("0".to_owned()));
+    }
+
+    #[test]
+    fn test_select_matching_and_invert() {
+        let mut lines = lines_with_content("NAME\nfoo-1\nbar\nfoo-2\nbaz", 1);
+        let selected = |lines: &Lines| {
+            lines
+                .get_cursor_line_and_selected_lines()
+                .map(|(_, selected_lines)| String::from(selected_lines))
+        };
+
+        lines.select_matching(&"^(foo|NAME)".parse().unwrap());
+        assert_eq!(selected(&lines), Some("foo-1\nfoo-2".to_owned()));
+
+        lines.unselect_matching(&"2$".parse().unwrap());
+        assert_eq!(selected(&lines), Some("foo-1".to_owned()));
+
+        lines.invert_selection();
+        assert_eq!(selected(&lines), Some("bar\nfoo-2\nbaz".to_owned()));
+    }
+
+    #[test]
+    fn test_range_selection() {
+        let mut lines = lines_with_content("header\na\nb\nc\nd\ne", 1);
+        let selected = |lines: &Lines| {
+            lines
+                .get_cursor_line_and_selected_lines()
+                .map(|(_, selected_lines)| String::from(selected_lines))
+        };
+
+        lines.move_cursor_down(3);
+        lines.select_current();
+        lines.move_cursor_up(2);
+        lines.start_range_selection();
+        lines.move_cursor_down(1);
+        assert_eq!(selected(&lines), Some("b\nc\nd".to_owned()));
+
+        // Moving the cursor above the anchor shrinks the range on the other
+        // side, but keeps lines selected before the range selection.
+        lines.move_cursor_up(2);
+        assert_eq!(selected(&lines), Some("a\nb\nd".to_owned()));
+
+        lines.end_range_selection();
+        lines.move_cursor_to_last_line();
+        assert_eq!(selected(&lines), Some("a\nb\nd".to_owned()));
+    }
+
+    #[test]
+    fn test_cursor_position_of_total() {
+        let mut lines = lines_with_content("header\nfoo\nbar\nfoo2", 1);
+        assert_eq!(lines.cursor_position_of_total(), Some((1, 3)));
+
+        lines.set_filter_pattern(Some("foo".to_owned()));
+        lines.move_cursor_to_last_line();
+        assert_eq!(lines.cursor_position_of_total(), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_filter_persists_across_updates() {
+        let mut lines = lines_with_content("foo\nbar", 0);
+        lines.set_filter_pattern(Some("ba".to_owned()));
+        assert_eq!(lines.get_cursor_position(), Some(1));
+
+        lines.update_lines("baz\nfoo\nbar".to_owned()).unwrap();
+        assert_eq!(lines.visible_indices, vec![0, 2]);
+        assert_eq!(lines.get_cursor_position(), Some(2));
+
+        lines.set_filter_pattern(None);
+        assert_eq!(lines.visible_indices, vec![0, 1, 2]);
+        assert_eq!(lines.get_cursor_position(), Some(2));
+    }
+
+    #[test]
+    fn test_selections_follow_line_content_across_updates() {
+        let selected = |lines: &Lines| {
+            lines
+                .get_cursor_line_and_selected_lines()
+                .map(|(_, selected_lines)| String::from(selected_lines))
+        };
+
+        let mut lines = lines_with_identity(
+            "header\na\nb\nc",
+            1,
+            LineIdentity::Line,
+            LineIdentity::Index,
+        );
+        lines.select_current();
+        lines.move_cursor_down(1);
+        lines.select_current();
+
+        // Lines are selected at their new position, and a selected line that
+        // disappeared stays unselected.
+        lines.update_lines("header\nx\nc\nb".to_owned()).unwrap();
+        assert_eq!(selected(&lines), Some("b".to_owned()));
+
+        let mut lines = lines_with_identity(
+            "1 a\n2 b\n3 c",
+            0,
+            LineIdentity::Field(1),
+            LineIdentity::Index,
+        );
+        lines.select_current();
+        lines
+            .update_lines("0 z\n1 a-changed\n2 b".to_owned())
+            .unwrap();
+        assert_eq!(selected(&lines), Some("1 a-changed".to_owned()));
+
+        // By default, selections stay at their position.
+        let mut lines = lines_with_content("a\nb", 0);
+        lines.select_current();
+        lines.update_lines("b\na".to_owned()).unwrap();
+        assert_eq!(selected(&lines), Some("b".to_owned()));
+    }
+
+    #[test]
+    fn test_cursor_follows_line_content_across_updates() {
+        let cursor_line = |lines: &Lines| {
+            lines
+                .get_cursor_line_and_selected_lines()
+                .map(|(cursor_line, _)| String::from(cursor_line))
+        };
+
+        let mut lines = lines_with_identity("a\nb\nc", 0, LineIdentity::Index, LineIdentity::Line);
+        lines.move_cursor_down(1);
+        lines.update_lines("x\na\nb\nc".to_owned()).unwrap();
+        assert_eq!(cursor_line(&lines), Some("b".to_owned()));
+
+        // Of several lines with the same content, the nearest one is chosen.
+        lines.update_lines("b\nx\ny\nb\nz".to_owned()).unwrap();
+        assert_eq!(lines.get_cursor_position(), Some(3));
+
+        // If the line disappeared, the cursor stays at the same position.
+        lines.update_lines("a\nc\nd\ne".to_owned()).unwrap();
+        assert_eq!(lines.get_cursor_position(), Some(3));
+    }
+
+    #[test]
+    fn test_highlight_changes() {
+        let changed = |lines: &Lines| {
+            (0..lines.lines.len())
+                .filter(|&i| lines.changed_at[i].is_some())
+                .collect_vec()
+        };
+
+        let mut lines = lines_with_content("header\na\nb\nc", 1);
+        lines.highlight_changes = true;
+        lines
+            .update_lines("header2\na\nx\nc\nd".to_owned())
+            .unwrap();
+        assert_eq!(changed(&lines), vec![2, 4]);
+
+        // Without a duration, changes are only highlighted until the next
+        // update.
+        lines
+            .update_lines("header2\na\nx\nc\nd".to_owned())
+            .unwrap();
+        assert_eq!(changed(&lines), Vec::<usize>::new());
+
+        // With a duration, changes stay highlighted across updates until
+        // the duration expires.
+        lines.highlight_changes_duration = Some(Duration::from_secs(60));
+        lines
+            .update_lines("header2\ny\nx\nc\nd".to_owned())
+            .unwrap();
+        lines
+            .update_lines("header2\ny\nx\nc\nd".to_owned())
+            .unwrap();
+        assert_eq!(changed(&lines), vec![1]);
+        assert!(lines.next_change_expiry().is_some());
+
+        lines.highlight_changes_duration = Some(Duration::ZERO);
+        lines.expire_changes();
+        assert_eq!(changed(&lines), Vec::<usize>::new());
+        assert_eq!(lines.next_change_expiry(), None);
+    }
+
+    #[test]
+    fn test_highlight_changes_by_identity() {
+        let changed = |lines: &Lines| {
+            (0..lines.lines.len())
+                .filter(|&i| lines.changed_at[i].is_some())
+                .collect_vec()
+        };
+
+        // Lines shifted by an inserted line are not highlighted.
+        let mut lines = lines_with_identity("a\nb\nc", 0, LineIdentity::Line, LineIdentity::Line);
+        lines.highlight_changes = true;
+        lines.update_lines("a\nx\nb\nc".to_owned()).unwrap();
+        assert_eq!(changed(&lines), vec![1]);
+
+        // Lines identified by a field are highlighted if their other fields
+        // changed, and their highlight moves with them.
+        let mut lines = lines_with_identity(
+            "1 running\n2 running",
+            0,
+            LineIdentity::Field(1),
+            LineIdentity::Line,
+        );
+        lines.highlight_changes = true;
+        lines.highlight_changes_duration = Some(Duration::from_secs(60));
+        lines
+            .update_lines("1 running\n2 stopped".to_owned())
+            .unwrap();
+        assert_eq!(changed(&lines), vec![1]);
+        lines
+            .update_lines("0 running\n1 running\n2 stopped".to_owned())
+            .unwrap();
+        assert_eq!(changed(&lines), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_is_cursor_on_last_line() {
+        let mut lines = lines_with_content("header", 1);
+        assert!(lines.is_cursor_on_last_line());
+
+        lines.update_lines("header\na\nb".to_owned()).unwrap();
+        assert!(!lines.is_cursor_on_last_line());
+
+        lines.move_cursor_to_last_line();
+        assert!(lines.is_cursor_on_last_line());
+    }
 }
@@ -1,7 +1,9 @@
 mod selected_line;
 
 use derive_new::new;
+use itertools::izip;
 use ratatui::style::Style;
+use std::collections::HashMap;
 
 use self::selected_line::LineSelected;
 
@@ -15,21 +17,127 @@ pub struct LineSelections {
     selected_style: Style,
     unselected_style: Style,
     index_after_header_lines: usize,
+    /// The range selection that is currently in progress, if any.
+    #[new(default)]
+    range_selection: Option<RangeSelection>,
+}
+
+/// A contiguous selection of lines from an anchor line to the cursor line,
+/// which is extended while the cursor moves.
+struct RangeSelection {
+    /// The index of the line at which the range selection was started.
+    anchor: usize,
+    /// The selections before the range selection was started, which are
+    /// restored for lines that leave the range.
+    selections_before: Vec<LineSelection>,
 }
 
 impl LineSelections {
-    /// Get referencing iterator.
-    pub fn iter(&self) -> impl Iterator<Item = &LineSelection> {
-        self.selections.iter()
+    /// Get the line selection at `index`, or `None` if it doesn't exist.
+    pub fn get(&self, index: usize) -> Option<&LineSelection> {
+        self.selections.get(index)
     }
 
     /// Resize the line selections to `new_len`.
     pub fn resize(&mut self, new_len: usize) {
-        self.selections.resize(
-            new_len,
-            // If larger, extend vector with unselected lines.
-            LineSelection::new(LineSelected::Unselected, self.unselected_style),
-        )
+        // If larger, extend vector with unselected lines.
+        let unselected = LineSelection::new(LineSelected::Unselected, self.unselected_style);
+        if let Some(range_selection) = &mut self.range_selection {
+            range_selection
+                .selections_before
+                .resize(new_len, unselected.clone());
+        }
+        self.selections.resize(new_len, unselected)
+    }
+
+    /// Move the selections onto new lines that replace the old lines, by
+    /// matching the keys identifying the lines: `old_keys` of the old lines
+    /// and `new_keys` of the new lines. Lines without a key are unselected. A
+    /// range selection in progress continues from the new line of its anchor,
+    /// or ends if there is none.
+    pub fn remap(&mut self, old_keys: &[Option<&str>], new_keys: &[Option<&str>]) {
+        self.selections = self.remap_selections(&self.selections, old_keys, new_keys);
+
+        if let Some(range_selection) = self.range_selection.take() {
+            let new_anchor = old_keys
+                .get(range_selection.anchor)
+                .copied()
+                .flatten()
+                .and_then(|anchor_key| new_keys.iter().position(|&key| key == Some(anchor_key)));
+            if let Some(anchor) = new_anchor {
+                let selections_before =
+                    self.remap_selections(&range_selection.selections_before, old_keys, new_keys);
+                self.range_selection = Some(RangeSelection {
+                    anchor,
+                    selections_before,
+                });
+            }
+        }
+    }
+
+    /// Create the selections of the new lines identified by `new_keys`, where
+    /// a new line is selected if an old line with the same key was selected.
+    /// If there are several old lines with the same key, only as many new
+    /// lines with that key are selected.
+    fn remap_selections(
+        &self,
+        selections: &[LineSelection],
+        old_keys: &[Option<&str>],
+        new_keys: &[Option<&str>],
+    ) -> Vec<LineSelection> {
+        let mut selected_counts: HashMap<&str, usize> = HashMap::new();
+        for (selection, key) in izip!(selections, old_keys) {
+            if let (true, Some(key)) = (selection.is_selected(), key) {
+                *selected_counts.entry(key).or_default() += 1;
+            }
+        }
+
+        new_keys
+            .iter()
+            .map(|key| {
+                let count = key.and_then(|key| selected_counts.get_mut(key));
+                match count {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        LineSelection::new(LineSelected::Selected, self.selected_style)
+                    }
+                    _ => LineSelection::new(LineSelected::Unselected, self.unselected_style),
+                }
+            })
+            .collect()
+    }
+
+    /// Start a range selection at the line at `anchor`.
+    pub fn start_range(&mut self, anchor: usize) {
+        self.range_selection = Some(RangeSelection {
+            anchor,
+            selections_before: self.selections.clone(),
+        });
+    }
+
+    /// End the range selection, keeping the lines in the range selected.
+    pub fn end_range(&mut self) {
+        self.range_selection = None;
+    }
+
+    /// Get the index of the line at which the range selection was started,
+    /// or `None` if no range selection is in progress.
+    pub fn range_anchor(&self) -> Option<usize> {
+        self.range_selection
+            .as_ref()
+            .map(|range_selection| range_selection.anchor)
+    }
+
+    /// Update the range selection in progress to consist of the lines at
+    /// `indices`, in addition to the lines selected before it was started.
+    pub fn select_range(&mut self, indices: impl IntoIterator<Item = usize>) {
+        if let Some(range_selection) = &self.range_selection {
+            self.selections
+                .clone_from(&range_selection.selections_before);
+            for index in indices {
+                self.select_at_index(index);
+            }
+        }
     }
 
     /// Select all lines.
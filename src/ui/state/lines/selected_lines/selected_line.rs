@@ -28,7 +28,7 @@ impl<'a> LineSelection {
     }
 
     /// Draw line selection as a styled ratatui widget.
-    pub fn draw(&self) -> Cell {
+    pub fn draw(&self) -> Cell<'_> {
         self.displayed.clone()
     }
 
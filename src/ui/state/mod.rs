@@ -1,29 +1,136 @@
+mod confirm_dialog;
 mod env_variables;
+mod error_banner;
+mod error_message;
+mod error_popup;
 mod help_menu;
+mod history;
 mod lines;
+mod output_viewer;
+mod scrollable_popup;
+mod status_bar;
+mod text_input;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Error, Result};
+use itertools::Itertools;
 use once_cell::sync::Lazy;
-use ratatui::Frame;
-use std::sync::Arc;
+use ratatui::{
+    prelude::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::Paragraph,
+    Frame,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::Mutex;
 
 use crate::config::{
-    Fields, KeybindingsPrintable, OperationExecutable, Operations, OperationsParsed, Styles,
+    KeyEvent, KeybindingsPrintable, LinePattern, ModeName, OkExitCodes, OperationExecutable,
+    Operations, OperationsParsed, Shell,
 };
+use crate::utils::command::SubcommandOutput;
 
 use self::{
+    confirm_dialog::{ConfirmDialog, ConfirmState},
+    error_banner::ErrorBanner,
+    error_popup::ErrorPopup,
     help_menu::HelpMenu,
-    lines::{CursorLine, Lines, SelectedLines},
+    history::OutputHistory,
+    lines::{CursorLine, SearchDirection, SelectedLines},
+    output_viewer::OutputViewer,
+    status_bar::LinesStatus,
+    text_input::{TextInput, TextInputState},
 };
 
 pub use self::env_variables::{EnvVariable, EnvVariables};
+pub use self::lines::Lines;
+pub use self::status_bar::StatusBar;
 
 pub struct State {
     mode: Mode,
     lines: Lines,
+    /// The past outputs of the watched command.
+    history: OutputHistory,
+    /// Whether the output of the watched command is streamed, in which case
+    /// the cursor follows new lines appended to the output if it is on the
+    /// last line, and each stream is a single output in the history.
+    streaming: bool,
     help_menu: HelpMenu,
     pub env_variables: Arc<Mutex<EnvVariables>>,
+    /// The text input used for reading user input into env variables.
+    env_input: TextInput,
+    /// The text input used for typing search patterns.
+    search_input: TextInput,
+    /// The text input used for typing filter patterns.
+    filter_input: TextInput,
+    /// What the user is currently typing into a text input for, or `None` if
+    /// no text input is shown.
+    text_input_purpose: Option<TextInputPurpose>,
+    /// The user-defined mode whose keybindings are currently active, or
+    /// `None` if the default keybindings are active.
+    active_mode: Option<ModeName>,
+    /// The error that is currently displayed, or `None` if no error is shown.
+    error_popup: Option<ErrorPopup>,
+    /// The error of the last execution of the watched command, or `None` if
+    /// it succeeded.
+    command_error: Option<ErrorBanner>,
+    /// The viewer of a subcommand's output that is currently displayed, or
+    /// `None` if no output is shown.
+    output_viewer: Option<OutputViewer>,
+    /// The status bar below the lines, or `None` if it's disabled.
+    status_bar: Option<StatusBar>,
+    /// Whether the periodic execution of the watched command is paused.
+    paused: bool,
+    /// The terminal row at which a mouse button was last pressed, if any.
+    mouse_row: Option<u16>,
+}
+
+/// The purpose for which the user is currently typing into a text input.
+enum TextInputPurpose {
+    /// The submitted input is saved to an env variable.
+    ReadIntoEnv(EnvVariable),
+    /// The input is the pattern that is searched for. Contains the cursor
+    /// position from before the search, which is restored on cancel.
+    Search(Option<usize>),
+    /// The input is the pattern that lines are filtered by.
+    Filter,
+    /// The input is the answer to a yes/no question.
+    Confirm(ConfirmDialog),
+}
+
+/// The state of the text input or confirm dialog shown to the user after
+/// handling a key event.
+pub enum PromptState {
+    /// The user is still typing or hasn't answered yet.
+    Pending,
+    /// The user has submitted the input or confirmed the dialog, so the
+    /// remaining operations are executed.
+    Completed,
+    /// The user has cancelled the input or declined the dialog, so the
+    /// remaining operations are aborted.
+    Aborted,
+}
+
+impl From<TextInputState> for PromptState {
+    fn from(state: TextInputState) -> Self {
+        match state {
+            TextInputState::Editing => Self::Pending,
+            TextInputState::Submitted(_) => Self::Completed,
+            TextInputState::Cancelled => Self::Aborted,
+        }
+    }
+}
+
+impl From<ConfirmState> for PromptState {
+    fn from(state: ConfirmState) -> Self {
+        match state {
+            ConfirmState::Pending => Self::Pending,
+            ConfirmState::Confirmed => Self::Completed,
+            ConfirmState::Declined => Self::Aborted,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -35,23 +142,79 @@ enum Mode {
 
 impl State {
     pub fn new(
-        header_lines: usize,
-        fields: Fields,
-        styles: Styles,
+        lines: Lines,
+        history_size: usize,
+        streaming: bool,
         keybindings_str: KeybindingsPrintable,
-        env_variables: EnvVariables,
+        mode_keybindings_str: Vec<(ModeName, KeybindingsPrintable)>,
+        status_bar: Option<StatusBar>,
     ) -> Self {
-        let env_variables = Arc::new(Mutex::new(env_variables));
+        let env_variables = Arc::new(Mutex::new(EnvVariables::new()));
         Self {
             mode: Mode::default(),
-            lines: Lines::new(fields, styles, header_lines),
-            help_menu: HelpMenu::new(keybindings_str, env_variables.clone()),
+            lines,
+            history: OutputHistory::new(history_size),
+            streaming,
+            help_menu: HelpMenu::new(keybindings_str, mode_keybindings_str, env_variables.clone()),
             env_variables,
+            env_input: TextInput::default(),
+            search_input: TextInput::default(),
+            filter_input: TextInput::default(),
+            text_input_purpose: None,
+            active_mode: None,
+            error_popup: None,
+            command_error: None,
+            output_viewer: None,
+            status_bar,
+            paused: false,
+            mouse_row: None,
         }
     }
 
     pub fn draw(&mut self, frame: &mut Frame) {
-        self.lines.render(frame);
+        match &self.status_bar {
+            Some(status_bar) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(frame.size());
+
+                render_lines(
+                    frame,
+                    chunks[0],
+                    &mut self.lines,
+                    self.command_error.as_ref(),
+                );
+
+                let lines_status = LinesStatus {
+                    mode: self.active_mode.as_ref(),
+                    filter_pattern: self.lines.filter_pattern(),
+                    selected_lines: self.lines.selected_count(),
+                    cursor_position: self.lines.cursor_position_of_total(),
+                };
+                status_bar.render(frame, chunks[1], lines_status);
+            }
+            None => {
+                render_lines(
+                    frame,
+                    frame.size(),
+                    &mut self.lines,
+                    self.command_error.as_ref(),
+                );
+
+                let indicators = self
+                    .history
+                    .position()
+                    .map(|history| history.describe(Instant::now()))
+                    .into_iter()
+                    .chain(self.paused.then(|| "paused".to_owned()))
+                    .chain(self.active_mode.as_ref().map(ToString::to_string))
+                    .join(" | ");
+                if !indicators.is_empty() {
+                    render_corner_indicator(frame, &indicators);
+                }
+            }
+        }
 
         if let Mode::HelpMenu = self.mode {
             // TODO: ratatui: how to constrain widget to certain custom frame
@@ -61,14 +224,77 @@ impl State {
             log::info!("full-screen frame width: {:?}", &frame.size().width);
             self.help_menu.render(frame);
         }
+
+        match &self.text_input_purpose {
+            Some(TextInputPurpose::ReadIntoEnv(env)) => {
+                let title = format!("read-into-env {}", env);
+                self.env_input.render(frame, &title);
+            }
+            Some(TextInputPurpose::Search(_)) => self.search_input.render(frame, "search"),
+            Some(TextInputPurpose::Filter) => self.filter_input.render(frame, "filter"),
+            Some(TextInputPurpose::Confirm(dialog)) => dialog.render(frame),
+            None => {}
+        }
+
+        if let Some(output_viewer) = &mut self.output_viewer {
+            output_viewer.render(frame);
+        }
+
+        if let Some(error_popup) = &self.error_popup {
+            error_popup.render(frame);
+        }
+    }
+}
+
+/// Render the `lines` to the `area` of the frame, with the error banner of the
+/// failed watched command below them, if any.
+fn render_lines(
+    frame: &mut Frame,
+    area: Rect,
+    lines: &mut Lines,
+    command_error: Option<&ErrorBanner>,
+) {
+    match command_error {
+        Some(command_error) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(command_error.height(area.height)),
+                ])
+                .split(area);
+
+            lines.render(frame, chunks[0]);
+            command_error.render(frame, chunks[1]);
+        }
+        None => lines.render(frame, area),
     }
 }
 
+/// Render a short text, e.g. the name of the active user-defined mode, in the
+/// bottom right corner of the frame.
+fn render_corner_indicator(frame: &mut Frame, text: &str) {
+    let text = format!(" {} ", text);
+    let size = frame.size();
+    let width = (text.chars().count() as u16).min(size.width);
+    let area = Rect::new(
+        size.x + size.width - width,
+        size.y + size.height.saturating_sub(1),
+        width,
+        size.height.min(1),
+    );
+
+    let paragraph = Paragraph::new(text).style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_widget(paragraph, area);
+}
+
 // TODO: replace with std::LazyLock once stable
 static CURSOR_LINE_ENV_VAR: Lazy<EnvVariable> =
     Lazy::new(|| "line".parse().expect("should be valid env var"));
 static SELECTED_LINES_ENV_VAR: Lazy<EnvVariable> =
     Lazy::new(|| "lines".parse().expect("should be valid env var"));
+static EXIT_CODE_ENV_VAR: Lazy<EnvVariable> =
+    Lazy::new(|| "status".parse().expect("should be valid env var"));
 
 // API for Lines
 impl State {
@@ -94,7 +320,66 @@ impl State {
     }
 
     pub fn update_lines(&mut self, new_lines: String) -> Result<()> {
-        self.lines.update_lines(new_lines)
+        // While an older output from the history is displayed, new outputs
+        // are only added to the history.
+        let displayed = if !self.streaming {
+            self.history.push(new_lines)
+        } else if new_lines.is_empty() {
+            // The output is reset when the stream is (re)started, which isn't
+            // recorded in the history.
+            self.history.start_stream();
+            self.history.is_live().then_some("")
+        } else {
+            self.history.push_streamed(new_lines)
+        };
+        if let Some(displayed) = displayed {
+            let follow = self.streaming && self.lines.is_cursor_on_last_line();
+            self.lines.update_lines(displayed.to_owned())?;
+            if follow {
+                self.lines.move_cursor_to_last_line();
+            }
+        }
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.refreshed();
+            status_bar.set_history(self.history.position());
+        }
+        Ok(())
+    }
+
+    /// Display the previous output of the watched command from the history.
+    pub fn history_prev(&mut self) -> Result<()> {
+        let displayed = self.history.prev().map(str::to_owned);
+        self.display_from_history(displayed)
+    }
+
+    /// Display the next output of the watched command from the history, or
+    /// the latest output live once it is reached.
+    pub fn history_next(&mut self) -> Result<()> {
+        let displayed = self.history.next().map(str::to_owned);
+        self.display_from_history(displayed)
+    }
+
+    /// Display the latest output of the watched command live.
+    pub fn history_live(&mut self) -> Result<()> {
+        let displayed = self.history.live().map(str::to_owned);
+        self.display_from_history(displayed)
+    }
+
+    /// Display the output from the history that was navigated to, if any.
+    fn display_from_history(&mut self, displayed: Option<String>) -> Result<()> {
+        if let Some(displayed) = displayed {
+            self.lines.update_lines(displayed)?;
+        }
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.set_history(self.history.position());
+        }
+        Ok(())
+    }
+
+    /// Get the time until the next highlight of a changed line expires, or
+    /// `None` if no highlight expires.
+    pub fn next_change_expiry(&self) -> Option<Duration> {
+        self.lines.next_change_expiry()
     }
 
     pub fn get_cursor_line_and_selected_lines(&mut self) -> Option<(CursorLine, SelectedLines)> {
@@ -121,6 +406,30 @@ impl State {
         self.lines.unselect_all();
     }
 
+    pub fn select_matching(&mut self, pattern: &LinePattern) {
+        self.lines.select_matching(pattern);
+    }
+
+    pub fn unselect_matching(&mut self, pattern: &LinePattern) {
+        self.lines.unselect_matching(pattern);
+    }
+
+    pub fn invert_selection(&mut self) {
+        self.lines.invert_selection();
+    }
+
+    pub fn start_range_selection(&mut self) {
+        self.lines.start_range_selection();
+    }
+
+    pub fn end_range_selection(&mut self) {
+        self.lines.end_range_selection();
+    }
+
+    pub fn toggle_range_selection(&mut self) {
+        self.lines.toggle_range_selection();
+    }
+
     // API for Help Menu
 
     pub async fn show_help_menu(&mut self) {
@@ -172,6 +481,70 @@ impl State {
         }
     }
 
+    pub fn move_page_down(&mut self) {
+        match self.mode {
+            Mode::Normal => self.lines.move_cursor_page_down(),
+            Mode::HelpMenu => self.help_menu.move_down(self.help_menu.viewport_height()),
+        }
+    }
+
+    pub fn move_page_up(&mut self) {
+        match self.mode {
+            Mode::Normal => self.lines.move_cursor_page_up(),
+            Mode::HelpMenu => self.help_menu.move_up(self.help_menu.viewport_height()),
+        }
+    }
+
+    pub fn move_half_page_down(&mut self) {
+        match self.mode {
+            Mode::Normal => self.lines.move_cursor_half_page_down(),
+            Mode::HelpMenu => self
+                .help_menu
+                .move_down((self.help_menu.viewport_height() / 2).max(1)),
+        }
+    }
+
+    pub fn move_half_page_up(&mut self) {
+        match self.mode {
+            Mode::Normal => self.lines.move_cursor_half_page_up(),
+            Mode::HelpMenu => self
+                .help_menu
+                .move_up((self.help_menu.viewport_height() / 2).max(1)),
+        }
+    }
+
+    // The help menu has no cursor, so these only apply to the lines.
+
+    pub fn move_to_screen_top(&mut self) {
+        if let Mode::Normal = self.mode {
+            self.lines.move_cursor_to_screen_top();
+        }
+    }
+
+    pub fn move_to_screen_middle(&mut self) {
+        if let Mode::Normal = self.mode {
+            self.lines.move_cursor_to_screen_middle();
+        }
+    }
+
+    pub fn move_to_screen_bottom(&mut self) {
+        if let Mode::Normal = self.mode {
+            self.lines.move_cursor_to_screen_bottom();
+        }
+    }
+
+    /// Move the cursor to the line at which a mouse button was last pressed.
+    pub fn move_to_mouse(&mut self) {
+        if let (Mode::Normal, Some(row)) = (&self.mode, self.mouse_row) {
+            self.lines.move_cursor_to_screen_row(row);
+        }
+    }
+
+    /// Record the terminal `row` at which a mouse button has been pressed.
+    pub fn set_mouse_row(&mut self, row: u16) {
+        self.mouse_row = Some(row);
+    }
+
     // API for environment variables
 
     /// Generate initial environment variables. The commands to set each
@@ -179,9 +552,17 @@ impl State {
     pub async fn generate_initial_env_vars(
         &mut self,
         initial_env_ops_parsed: OperationsParsed,
+        shell: &Shell,
+        timeout: Option<Duration>,
+        ok_exit_codes: &OkExitCodes,
     ) -> Result<()> {
-        let initial_env_ops =
-            Operations::from_parsed(initial_env_ops_parsed.clone(), &self.get_env());
+        let initial_env_ops = Operations::from_parsed(
+            initial_env_ops_parsed.clone(),
+            &self.get_env(),
+            shell,
+            timeout,
+            ok_exit_codes,
+        );
 
         // TODO: consider trying to use async iterators to do this in one iterator pass (instead of the mut hashmap) once stable
         for (i, op) in initial_env_ops.into_iter().enumerate() {
@@ -220,6 +601,18 @@ impl State {
         env_variables.merge_new_envs(new_env_variables);
     }
 
+    /// Set the env variable for the last exit code of the watched command, or
+    /// unset it if the watched command exited without one.
+    pub async fn set_exit_code(&mut self, exit_code: Option<i32>) {
+        match exit_code {
+            Some(exit_code) => {
+                self.set_env((*EXIT_CODE_ENV_VAR).clone(), exit_code.to_string())
+                    .await
+            }
+            None => self.unset_env(&EXIT_CODE_ENV_VAR).await,
+        }
+    }
+
     /// Unset an environment variable.
     pub async fn unset_env(&mut self, env_var: &EnvVariable) {
         let mut env_variables = self.env_variables.lock().await;
@@ -234,7 +627,208 @@ impl State {
         }
     }
 
-    pub async fn read_into_env(&mut self, _env: &EnvVariable) {
-        todo!()
+    /// Show a text input, whose submitted value will be saved to the
+    /// environment variable `env`.
+    pub fn read_into_env(&mut self, env: &EnvVariable) {
+        self.env_input.reset();
+        self.text_input_purpose = Some(TextInputPurpose::ReadIntoEnv(env.clone()));
+    }
+
+    // API for text inputs
+
+    /// Handle a key pressed while the user is typing into a text input or
+    /// answering a confirm dialog. Once the input is submitted or cancelled,
+    /// or the dialog is answered, it is hidden.
+    pub async fn handle_text_input_key(&mut self, key: &KeyEvent) -> PromptState {
+        match self.text_input_purpose.take() {
+            Some(TextInputPurpose::ReadIntoEnv(env)) => {
+                let text_input_state = self.env_input.handle_key(key);
+                match &text_input_state {
+                    TextInputState::Editing => {
+                        self.text_input_purpose = Some(TextInputPurpose::ReadIntoEnv(env));
+                    }
+                    TextInputState::Submitted(value) => self.set_env(env, value.clone()).await,
+                    TextInputState::Cancelled => {}
+                };
+                text_input_state.into()
+            }
+            Some(TextInputPurpose::Search(cursor_position)) => {
+                let text_input_state = self.search_input.handle_key(key);
+                match &text_input_state {
+                    TextInputState::Editing => {
+                        // Search incrementally while the pattern is typed.
+                        self.lines
+                            .set_search_pattern(Some(self.search_input.input_string()));
+                        self.lines
+                            .move_cursor_to_match(SearchDirection::Forward, true);
+                        self.text_input_purpose = Some(TextInputPurpose::Search(cursor_position));
+                    }
+                    TextInputState::Submitted(pattern) => {
+                        self.lines.set_search_pattern(Some(pattern.clone()));
+                        self.lines
+                            .move_cursor_to_match(SearchDirection::Forward, true);
+                    }
+                    TextInputState::Cancelled => {
+                        self.lines.set_search_pattern(None);
+                        // Undo the cursor movements of the incremental search.
+                        if let Some(cursor_position) = cursor_position {
+                            self.lines.move_cursor_to_position(cursor_position);
+                        }
+                    }
+                };
+                text_input_state.into()
+            }
+            Some(TextInputPurpose::Filter) => {
+                let text_input_state = self.filter_input.handle_key(key);
+                match &text_input_state {
+                    TextInputState::Editing => {
+                        // Filter incrementally while the pattern is typed.
+                        self.lines
+                            .set_filter_pattern(Some(self.filter_input.input_string()));
+                        self.text_input_purpose = Some(TextInputPurpose::Filter);
+                    }
+                    TextInputState::Submitted(pattern) => {
+                        self.lines.set_filter_pattern(Some(pattern.clone()));
+                    }
+                    TextInputState::Cancelled => self.lines.set_filter_pattern(None),
+                };
+                text_input_state.into()
+            }
+            Some(TextInputPurpose::Confirm(dialog)) => {
+                let confirm_state = dialog.handle_key(key);
+                if let ConfirmState::Pending = confirm_state {
+                    self.text_input_purpose = Some(TextInputPurpose::Confirm(dialog));
+                }
+                confirm_state.into()
+            }
+            // No text input is shown, so the key press is ignored.
+            None => PromptState::Aborted,
+        }
+    }
+
+    // API for searching
+
+    /// Show a text input for typing a search pattern.
+    pub fn search(&mut self) {
+        self.search_input.reset();
+        self.text_input_purpose = Some(TextInputPurpose::Search(self.lines.get_cursor_position()));
+    }
+
+    /// Move the cursor to the next line matching the search pattern.
+    pub fn search_next(&mut self) {
+        self.lines
+            .move_cursor_to_match(SearchDirection::Forward, false);
+    }
+
+    /// Move the cursor to the previous line matching the search pattern.
+    pub fn search_prev(&mut self) {
+        self.lines
+            .move_cursor_to_match(SearchDirection::Backward, false);
+    }
+
+    // API for filtering
+
+    /// Show a text input for typing a filter pattern.
+    pub fn filter(&mut self) {
+        self.filter_input.reset();
+        self.text_input_purpose = Some(TextInputPurpose::Filter);
+    }
+
+    // API for confirmation
+
+    /// Show a dialog asking the user to confirm the `message`, in which all
+    /// references to environment variables, including the cursor line and
+    /// selected lines, are expanded.
+    pub async fn confirm(&mut self, message: &str) {
+        self.add_cursor_and_selected_lines_to_env().await;
+        let message = self.env_variables.lock().await.expand(message);
+        self.remove_cursor_and_selected_lines_from_env().await;
+
+        self.text_input_purpose = Some(TextInputPurpose::Confirm(ConfirmDialog::new(message)));
+    }
+
+    // API for modes
+
+    /// Activate the keybindings of the user-defined `mode`.
+    pub fn enter_mode(&mut self, mode: ModeName) {
+        self.active_mode = Some(mode);
+    }
+
+    /// Activate the default keybindings.
+    pub fn exit_mode(&mut self) {
+        self.active_mode = None;
+    }
+
+    /// Get the user-defined mode whose keybindings are currently active, or
+    /// `None` if the default keybindings are active.
+    pub fn active_mode(&self) -> Option<&ModeName> {
+        self.active_mode.as_ref()
+    }
+
+    // API for errors
+
+    /// Show a popup displaying the `error`.
+    pub fn show_error(&mut self, error: &Error) {
+        self.error_popup = Some(ErrorPopup::new(error));
+    }
+
+    /// Hide the error popup.
+    pub fn hide_error(&mut self) {
+        self.error_popup = None;
+    }
+
+    /// Show a banner displaying the `error` of the failed watched command,
+    /// while its previous output stays displayed.
+    pub fn show_command_error(&mut self, error: &Error) {
+        self.command_error = Some(ErrorBanner::new(error));
+    }
+
+    /// Hide the error banner of the watched command.
+    pub fn hide_command_error(&mut self) {
+        self.command_error = None;
+    }
+
+    // API for output viewer
+
+    /// Show a popup displaying the `output` of a subcommand.
+    pub fn show_output(&mut self, output: SubcommandOutput) {
+        self.output_viewer = Some(OutputViewer::new(output));
+    }
+
+    /// Scroll the displayed output according to a key event. Returns whether
+    /// the key dismissed the output viewer, which is then hidden.
+    pub fn handle_output_viewer_key(&mut self, key: &KeyEvent) -> bool {
+        let dismissed = self
+            .output_viewer
+            .as_mut()
+            .is_none_or(|output_viewer| output_viewer.handle_key(key));
+        if dismissed {
+            self.output_viewer = None;
+        }
+        dismissed
+    }
+
+    // API for status bar
+
+    /// Returns whether the status bar is displayed.
+    pub fn has_status_bar(&self) -> bool {
+        self.status_bar.is_some()
+    }
+
+    /// Set whether the periodic execution of the watched command is
+    /// `paused`, and the `interval` between executions.
+    pub fn set_watching(&mut self, paused: bool, interval: Duration) {
+        self.paused = paused;
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.set_watching(paused, interval);
+        }
+    }
+
+    /// Set the operation that is currently blocking, or `None` if nothing is
+    /// blocking anymore.
+    pub fn set_activity(&mut self, activity: Option<String>) {
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.set_activity(activity);
+        }
     }
 }
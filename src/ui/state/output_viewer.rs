@@ -0,0 +1,100 @@
+use ratatui::{text::Text, Frame};
+
+use crate::config::{KeyCode, KeyEvent, KeyModifier};
+use crate::utils::command::SubcommandOutput;
+
+use super::{help_menu::centered_rect, scrollable_popup::ScrollablePopup};
+
+/// A popup displaying the stdout and stderr of a subcommand, which can be
+/// scrolled through and is shown until dismissed by the user.
+pub struct OutputViewer {
+    output: String,
+    popup: ScrollablePopup,
+}
+
+impl OutputViewer {
+    pub fn new(SubcommandOutput { stdout, stderr }: SubcommandOutput) -> Self {
+        // Show stderr in its own section below stdout, if there is any.
+        let mut output = stdout;
+        if !stderr.is_empty() {
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push_str("--STDERR--\n");
+            output.push_str(&stderr);
+        }
+        Self {
+            output,
+            popup: ScrollablePopup::default(),
+        }
+    }
+
+    /// Scroll the output according to a key event. Returns whether the key
+    /// dismissed the viewer.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let page = self.popup.viewport_height();
+        match (key.modifier(), key.code()) {
+            (KeyModifier::None, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter) => {
+                return true;
+            }
+            (KeyModifier::None, KeyCode::Char('j') | KeyCode::Down) => self.popup.move_down(1),
+            (KeyModifier::None, KeyCode::Char('k') | KeyCode::Up) => self.popup.move_up(1),
+            (KeyModifier::None, KeyCode::Space | KeyCode::PageDown) => self.popup.move_down(page),
+            (KeyModifier::None, KeyCode::PageUp) => self.popup.move_up(page),
+            (KeyModifier::None, KeyCode::Char('g') | KeyCode::Home) => self.popup.scroll_to(0),
+            (KeyModifier::None, KeyCode::Char('G') | KeyCode::End) => {
+                let last_page = self.output.lines().count().saturating_sub(page);
+                self.popup.scroll_to(last_page);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Render to frame.
+    pub fn render(&mut self, frame: &mut Frame) {
+        let popup_area = centered_rect(90, 90, frame.size());
+        let title = "output (q to dismiss)";
+        self.popup
+            .render(frame, popup_area, title, Text::raw(self.output.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(stdout: &str, stderr: &str) -> SubcommandOutput {
+        SubcommandOutput {
+            stdout: stdout.to_owned(),
+            stderr: stderr.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_output_with_stderr() {
+        assert_eq!(OutputViewer::new(output("out\n", "")).output, "out\n");
+        assert_eq!(
+            OutputViewer::new(output("out", "err\n")).output,
+            "out\n--STDERR--\nerr\n"
+        );
+        assert_eq!(
+            OutputViewer::new(output("", "err\n")).output,
+            "--STDERR--\nerr\n"
+        );
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut viewer = OutputViewer::new(output("first\nsecond\nthird", ""));
+        let mut handle = |key: &str| viewer.handle_key(&key.parse().unwrap());
+
+        assert!(!handle("j"));
+        assert!(!handle("G"));
+        assert!(!handle("pageup"));
+        assert!(!handle("x"));
+        assert!(handle("q"));
+        assert!(handle("esc"));
+        assert!(handle("enter"));
+    }
+}
@@ -0,0 +1,68 @@
+use ratatui::{
+    prelude::{Alignment, Margin, Rect},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+/// A popup displaying a text that can be scrolled vertically, e.g. the help
+/// menu.
+#[derive(Default)]
+pub struct ScrollablePopup {
+    vertical_scroll_index: usize,
+    vertical_scroll_state: ScrollbarState,
+    /// The number of text lines that fit into the popup when it was last
+    /// rendered.
+    viewport_height: usize,
+}
+
+impl ScrollablePopup {
+    /// Render the `text` into the `popup_area` of the frame, inside a border
+    /// with the `title`.
+    pub fn render(&mut self, frame: &mut Frame, popup_area: Rect, title: &str, text: Text) {
+        self.viewport_height = usize::from(popup_area.height.saturating_sub(2));
+        self.vertical_scroll_state = self.vertical_scroll_state.content_length(text.height());
+
+        // Render the paragraph with the updated scroll state
+        let paragraph = Paragraph::new(text)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .alignment(Alignment::Left)
+            // scroll offset for each axis: (y, x)
+            .scroll((self.vertical_scroll_index as u16, 0));
+
+        // Render the scrollbar next to the paragraph
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(paragraph, popup_area);
+
+        frame.render_stateful_widget(
+            Scrollbar::default()
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            popup_area.inner(&Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut self.vertical_scroll_state,
+        );
+    }
+
+    /// Scroll to the text line at `index`, which is then displayed at the top.
+    pub fn scroll_to(&mut self, index: usize) {
+        self.vertical_scroll_index = index;
+        self.vertical_scroll_state = self.vertical_scroll_state.position(index);
+    }
+
+    pub fn move_down(&mut self, steps: usize) {
+        self.scroll_to(self.vertical_scroll_index.saturating_add(steps));
+    }
+
+    pub fn move_up(&mut self, steps: usize) {
+        self.scroll_to(self.vertical_scroll_index.saturating_sub(steps));
+    }
+
+    /// Get the number of text lines that fit into the popup.
+    pub fn viewport_height(&self) -> usize {
+        self.viewport_height.max(1)
+    }
+}
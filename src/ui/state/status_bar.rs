@@ -0,0 +1,213 @@
+use ratatui::{
+    layout::Alignment,
+    prelude::Rect,
+    style::{Modifier, Style},
+    widgets::Paragraph,
+    Frame,
+};
+use std::time::{Duration, Instant};
+
+use crate::config::ModeName;
+
+use super::history::HistoryPosition;
+
+/// A single line below the lines, which displays the state of the watched
+/// command, what watchbind is currently doing, and information about the
+/// lines.
+pub struct StatusBar {
+    /// The time between executions of the watched command.
+    interval: Duration,
+    /// Whether the output of the watched command is streamed, instead of
+    /// the command being executed periodically.
+    streaming: bool,
+    /// Whether the periodic execution of the watched command is paused.
+    paused: bool,
+    /// The time of the last successful execution of the watched command, or
+    /// `None` if it hasn't completed yet.
+    last_refresh: Option<Instant>,
+    /// The operation watchbind is currently blocked by, or `None` if it's
+    /// not blocked.
+    activity: Option<String>,
+    /// The position of the displayed output in the output history, or `None`
+    /// if the latest output is displayed live.
+    history: Option<HistoryPosition>,
+}
+
+/// Information about the lines and keybindings, displayed on the right side
+/// of the status bar.
+pub struct LinesStatus<'a> {
+    pub mode: Option<&'a ModeName>,
+    pub filter_pattern: Option<&'a str>,
+    pub selected_lines: usize,
+    /// The 1-based position of the cursor, and the number of displayed lines.
+    pub cursor_position: Option<(usize, usize)>,
+}
+
+impl StatusBar {
+    pub fn new(interval: Duration, streaming: bool) -> Self {
+        Self {
+            interval,
+            streaming,
+            paused: false,
+            last_refresh: None,
+            activity: None,
+            history: None,
+        }
+    }
+
+    /// Render the status bar to the `area` of the frame.
+    pub fn render(&self, frame: &mut Frame, area: Rect, lines_status: LinesStatus) {
+        let style = Style::default().add_modifier(Modifier::REVERSED);
+
+        // Fill the entire line with the style first.
+        frame.render_widget(Paragraph::new("").style(style), area);
+        frame.render_widget(
+            Paragraph::new(self.watched_command_status(Instant::now())).style(style),
+            area,
+        );
+        frame.render_widget(
+            Paragraph::new(lines_status.to_string())
+                .style(style)
+                .alignment(Alignment::Right),
+            area,
+        );
+    }
+
+    /// Record that the watched command has just executed successfully.
+    pub fn refreshed(&mut self) {
+        self.last_refresh = Some(Instant::now());
+    }
+
+    /// Set the operation watchbind is currently blocked by, or `None` if
+    /// it's no longer blocked.
+    pub fn set_activity(&mut self, activity: Option<String>) {
+        self.activity = activity;
+    }
+
+    /// Set the position of the displayed output in the output history, or
+    /// `None` if the latest output is displayed live.
+    pub fn set_history(&mut self, history: Option<HistoryPosition>) {
+        self.history = history;
+    }
+
+    /// Set whether the periodic execution of the watched command is
+    /// `paused`, and the `interval` between executions.
+    pub fn set_watching(&mut self, paused: bool, interval: Duration) {
+        self.paused = paused;
+        self.interval = interval;
+    }
+
+    /// Describe the current activity and the watched command's state at the
+    /// point in time `now`.
+    fn watched_command_status(&self, now: Instant) -> String {
+        let activity = match &self.activity {
+            Some(operation) => format!("running: {}", operation),
+            None => "idle".to_owned(),
+        };
+        let last_refresh = match self.last_refresh {
+            Some(last_refresh) => format!(
+                "refreshed {} ago",
+                format_duration(now.saturating_duration_since(last_refresh))
+            ),
+            None => "not refreshed yet".to_owned(),
+        };
+        let interval = if self.streaming {
+            "streaming".to_owned()
+        } else if self.interval == Duration::ZERO {
+            "manual reload".to_owned()
+        } else {
+            format!("every {}s", self.interval.as_secs_f64())
+        };
+        let interval = if self.paused {
+            format!("paused ({})", interval)
+        } else {
+            interval
+        };
+        match self.history {
+            Some(history) => format!(
+                " {} | {} | {} | {}",
+                activity,
+                last_refresh,
+                interval,
+                history.describe(now)
+            ),
+            None => format!(" {} | {} | {}", activity, last_refresh, interval),
+        }
+    }
+}
+
+impl std::fmt::Display for LinesStatus<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(mode) = self.mode {
+            write!(f, "mode: {} | ", mode)?;
+        }
+        if let Some(filter_pattern) = self.filter_pattern {
+            write!(f, "filter: {} | ", filter_pattern)?;
+        }
+        write!(f, "{} selected | ", self.selected_lines)?;
+        match self.cursor_position {
+            Some((position, total)) => write!(f, "{}/{} ", position, total),
+            None => write!(f, "0/0 "),
+        }
+    }
+}
+
+/// Format a duration coarsely, in the largest fitting unit.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watched_command_status() {
+        let mut status_bar = StatusBar::new(Duration::from_secs_f64(0.5), false);
+        let now = Instant::now();
+        assert_eq!(
+            status_bar.watched_command_status(now),
+            " idle | not refreshed yet | every 0.5s"
+        );
+
+        status_bar.last_refresh = Some(now);
+        status_bar.set_activity(Some("exec -- make".to_owned()));
+        assert_eq!(
+            status_bar.watched_command_status(now + Duration::from_secs(125)),
+            " running: exec -- make | refreshed 2m ago | every 0.5s"
+        );
+
+        status_bar.set_activity(None);
+        status_bar.set_watching(true, Duration::from_secs(2));
+        assert_eq!(
+            status_bar.watched_command_status(now + Duration::from_secs(3)),
+            " idle | refreshed 3s ago | paused (every 2s)"
+        );
+
+        let status_bar = StatusBar::new(Duration::from_secs(2), true);
+        assert_eq!(
+            status_bar.watched_command_status(now),
+            " idle | not refreshed yet | streaming"
+        );
+    }
+
+    #[test]
+    fn test_lines_status() {
+        let mode: ModeName = "visual".parse().unwrap();
+        let lines_status = LinesStatus {
+            mode: Some(&mode),
+            filter_pattern: Some("foo"),
+            selected_lines: 2,
+            cursor_position: Some((3, 10)),
+        };
+        assert_eq!(
+            lines_status.to_string(),
+            "mode: visual | filter: foo | 2 selected | 3/10 "
+        );
+    }
+}
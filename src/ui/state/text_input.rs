@@ -0,0 +1,248 @@
+use ratatui::{
+    prelude::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::config::{KeyCode, KeyEvent, KeyModifier};
+
+/// A single-line text input field, which supports moving the cursor,
+/// editing text at the cursor and recalling previously submitted inputs.
+#[derive(Default)]
+pub struct TextInput {
+    /// The characters that have been typed so far.
+    input: Vec<char>,
+    /// The index in `input` at which new characters are inserted.
+    cursor_index: usize,
+    /// All previously submitted inputs, from oldest to newest.
+    history: Vec<String>,
+    /// The index in `history` of the entry that is currently being
+    /// displayed, or `None` if the user's own input is being displayed.
+    history_index: Option<usize>,
+    /// The user's own input, which is saved while browsing the history.
+    draft: Vec<char>,
+}
+
+/// The state of a `TextInput` after handling a key event.
+pub enum TextInputState {
+    /// The user is still editing the input.
+    Editing,
+    /// The user has confirmed the input.
+    Submitted(String),
+    /// The user has aborted the input.
+    Cancelled,
+}
+
+impl TextInput {
+    /// Clear the input, so it can be used for a new prompt. The history is
+    /// kept.
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.cursor_index = 0;
+        self.history_index = None;
+        self.draft.clear();
+    }
+
+    /// Get the current input as an owned String.
+    pub fn input_string(&self) -> String {
+        self.input.iter().collect()
+    }
+
+    /// Update the input according to a key event.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> TextInputState {
+        match (key.modifier(), key.code()) {
+            (KeyModifier::None, KeyCode::Enter) => {
+                let submitted = self.input_string();
+                self.add_to_history(submitted.clone());
+                self.reset();
+                return TextInputState::Submitted(submitted);
+            }
+            (KeyModifier::None, KeyCode::Esc) => {
+                self.reset();
+                return TextInputState::Cancelled;
+            }
+            (KeyModifier::None | KeyModifier::Shift, KeyCode::Char(c)) => self.insert(*c),
+            (KeyModifier::None, KeyCode::Space) => self.insert(' '),
+            (KeyModifier::None, KeyCode::Backspace) => self.delete_before_cursor(),
+            (KeyModifier::None, KeyCode::Delete) => self.delete_at_cursor(),
+            (KeyModifier::None, KeyCode::Left) => {
+                self.cursor_index = self.cursor_index.saturating_sub(1);
+            }
+            (KeyModifier::None, KeyCode::Right) => {
+                self.cursor_index = (self.cursor_index + 1).min(self.input.len());
+            }
+            (KeyModifier::None, KeyCode::Home) | (KeyModifier::Ctrl, KeyCode::Char('a')) => {
+                self.cursor_index = 0;
+            }
+            (KeyModifier::None, KeyCode::End) | (KeyModifier::Ctrl, KeyCode::Char('e')) => {
+                self.cursor_index = self.input.len();
+            }
+            (KeyModifier::Ctrl, KeyCode::Char('u')) => {
+                self.input.drain(..self.cursor_index);
+                self.cursor_index = 0;
+            }
+            (KeyModifier::None, KeyCode::Up) => self.history_older(),
+            (KeyModifier::None, KeyCode::Down) => self.history_newer(),
+            _ => {}
+        };
+        TextInputState::Editing
+    }
+
+    /// Render the text input with a `title` at the bottom of the frame.
+    pub fn render(&self, frame: &mut Frame, title: &str) {
+        let area = bottom_rect(3, frame.size());
+        // Get the inner width, so take borders into account.
+        let inner_width = area.width.saturating_sub(2) as usize;
+
+        // Scroll horizontally, so that the cursor is always visible.
+        let scroll = (self.cursor_index + 1).saturating_sub(inner_width);
+        let visible: String = self.input.iter().skip(scroll).collect();
+
+        let paragraph =
+            Paragraph::new(visible).block(Block::default().title(title).borders(Borders::ALL));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+
+        frame.set_cursor(area.x + 1 + (self.cursor_index - scroll) as u16, area.y + 1);
+    }
+
+    /// Insert a character at the cursor.
+    fn insert(&mut self, c: char) {
+        self.input.insert(self.cursor_index, c);
+        self.cursor_index += 1;
+    }
+
+    /// Delete the character before the cursor.
+    fn delete_before_cursor(&mut self) {
+        if self.cursor_index > 0 {
+            self.cursor_index -= 1;
+            self.input.remove(self.cursor_index);
+        }
+    }
+
+    /// Delete the character under the cursor.
+    fn delete_at_cursor(&mut self) {
+        if self.cursor_index < self.input.len() {
+            self.input.remove(self.cursor_index);
+        }
+    }
+
+    /// Add a submitted input to the history, unless it is identical to the
+    /// most recent entry.
+    fn add_to_history(&mut self, submitted: String) {
+        if self.history.last() != Some(&submitted) {
+            self.history.push(submitted);
+        }
+    }
+
+    /// Replace the input with the next older history entry.
+    fn history_older(&mut self) {
+        let new_index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.input.clone();
+                self.history.len() - 1
+            }
+            Some(i) => i.saturating_sub(1),
+        };
+        self.show_history_entry(Some(new_index));
+    }
+
+    /// Replace the input with the next newer history entry, or the user's
+    /// own input if there are no newer entries.
+    fn history_newer(&mut self) {
+        match self.history_index {
+            None => {}
+            Some(i) if i + 1 < self.history.len() => self.show_history_entry(Some(i + 1)),
+            Some(_) => self.show_history_entry(None),
+        }
+    }
+
+    /// Display the history entry at `index`, or the draft if `None`.
+    fn show_history_entry(&mut self, index: Option<usize>) {
+        self.history_index = index;
+        self.input = match index {
+            Some(i) => self.history[i].chars().collect(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.cursor_index = self.input.len();
+    }
+}
+
+/// Helper function to create a rect with a fixed `height` at the bottom of
+/// the available rect `r`.
+fn bottom_rect(height: u16, r: Rect) -> Rect {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(height)].as_ref())
+        .split(r)[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(text_input: &mut TextInput, key: &str) -> TextInputState {
+        text_input.handle_key(&key.parse().unwrap())
+    }
+
+    fn type_str(text_input: &mut TextInput, s: &str) {
+        for c in s.chars() {
+            text_input.insert(c);
+        }
+    }
+
+    #[test]
+    fn test_edit_at_cursor() {
+        let mut text_input = TextInput::default();
+        type_str(&mut text_input, "helo");
+        press(&mut text_input, "left");
+        press(&mut text_input, "l");
+        assert_eq!(text_input.input_string(), "hello");
+
+        press(&mut text_input, "home");
+        press(&mut text_input, "delete");
+        press(&mut text_input, "end");
+        press(&mut text_input, "backspace");
+        assert_eq!(text_input.input_string(), "ell");
+    }
+
+    #[test]
+    fn test_submit_and_cancel() {
+        let mut text_input = TextInput::default();
+        type_str(&mut text_input, "file.txt");
+        assert!(matches!(
+            press(&mut text_input, "enter"),
+            TextInputState::Submitted(s) if s == "file.txt"
+        ));
+        assert_eq!(text_input.input_string(), "");
+
+        type_str(&mut text_input, "abc");
+        assert!(matches!(
+            press(&mut text_input, "esc"),
+            TextInputState::Cancelled
+        ));
+        assert_eq!(text_input.input_string(), "");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut text_input = TextInput::default();
+        type_str(&mut text_input, "first");
+        press(&mut text_input, "enter");
+        type_str(&mut text_input, "second");
+        press(&mut text_input, "enter");
+
+        type_str(&mut text_input, "draft");
+        press(&mut text_input, "up");
+        assert_eq!(text_input.input_string(), "second");
+        press(&mut text_input, "up");
+        press(&mut text_input, "up");
+        assert_eq!(text_input.input_string(), "first");
+        press(&mut text_input, "down");
+        assert_eq!(text_input.input_string(), "second");
+        press(&mut text_input, "down");
+        assert_eq!(text_input.input_string(), "draft");
+    }
+}
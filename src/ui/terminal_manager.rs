@@ -1,6 +1,7 @@
 use anyhow::Result;
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Frame};
 use std::io::{stdout, Stdout};
@@ -12,13 +13,16 @@ type Terminal = ratatui::Terminal<Backend>;
 pub struct Tui {
     // TODO: don't make public (means moving the UI::draw! macro here)
     pub terminal: Terminal,
+    /// Whether mouse events are captured.
+    mouse: bool,
 }
 
 impl Tui {
-    /// Initialise and show a new TUI.
-    pub fn new() -> Result<Self> {
+    /// Initialise and show a new TUI, which captures mouse events if `mouse`
+    /// is enabled.
+    pub fn new(mouse: bool) -> Result<Self> {
         let terminal = Self::create_new_terminal()?;
-        let mut terminal_manager = Tui { terminal };
+        let mut terminal_manager = Tui { terminal, mouse };
 
         terminal_manager.show()?;
 
@@ -43,6 +47,9 @@ impl Tui {
     fn show(&mut self) -> Result<()> {
         enable_raw_mode()?;
         crossterm::execute!(self.terminal.backend_mut(), EnterAlternateScreen)?;
+        if self.mouse {
+            crossterm::execute!(self.terminal.backend_mut(), EnableMouseCapture)?;
+        }
         self.terminal.hide_cursor()?;
 
         Ok(())
@@ -65,6 +72,9 @@ impl Tui {
     /// split-second.
     pub fn hide(&mut self) -> Result<()> {
         disable_raw_mode()?;
+        if self.mouse {
+            crossterm::execute!(self.terminal.backend_mut(), DisableMouseCapture)?;
+        }
 
         // The trick to not unpainting our TUI is to not leave the alternate
         // screen like we would normally do when hiding the TUI.
@@ -78,6 +88,9 @@ impl Tui {
     /// to the user's terminal.
     fn exit(&mut self) -> Result<()> {
         disable_raw_mode()?;
+        if self.mouse {
+            crossterm::execute!(self.terminal.backend_mut(), DisableMouseCapture)?;
+        }
         crossterm::execute!(self.terminal.backend_mut(), LeaveAlternateScreen)?;
         self.terminal.show_cursor()?;
 
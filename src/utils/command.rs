@@ -1,16 +1,22 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    fmt,
+    fs::File,
+    io::IsTerminal,
     ops::Deref,
     process::{ExitStatus, Stdio},
     sync::Arc,
     time::Duration,
 };
-use tokio::process::Command as TokioCommand;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader, Lines};
+use tokio::process::{Child, ChildStderr, ChildStdout, Command as TokioCommand};
 use tokio::sync::mpsc::Receiver;
+use tokio::task::JoinHandle;
 use tokio::{io::AsyncReadExt, sync::Mutex};
 
+use crate::config::{CommandLine, OkExitCodes, Shell};
 use crate::ui::{EnvVariables, InterruptSignal};
 
 /// An async Command offering customization of the blocking behaviour, the input
@@ -18,7 +24,12 @@ use crate::ui::{EnvVariables, InterruptSignal};
 /// execution can be interrupted. Utilizes the type-state builder pattern to
 /// enforce these configurations at compile-time.
 pub struct CommandBuilder<B = NonBlocking, E = WithoutEnv, O = NoOutput, I = NonInterruptible> {
-    command: String,
+    command: CommandLine,
+    shell: Shell,
+    /// The duration after which a blocking command is killed, if any.
+    timeout: Option<Duration>,
+    /// The exit codes with which a blocking command is successful.
+    ok_exit_codes: OkExitCodes,
     blocking: B,
     output: O,
     interruptible: I,
@@ -69,9 +80,12 @@ pub struct Interruptible {
 
 // TODO: impl default trait so we don't have to duplicate this
 impl CommandBuilder {
-    pub fn new(command: String) -> Self {
+    pub fn new(command: CommandLine, shell: Shell) -> Self {
         CommandBuilder {
             command,
+            shell,
+            timeout: None,
+            ok_exit_codes: OkExitCodes::default(),
             blocking: NonBlocking,
             output: NoOutput,
             interruptible: NonInterruptible,
@@ -84,11 +98,26 @@ impl CommandBuilder {
 /// Since we can't save a `tokio::process::Command` permanently and clone it
 /// on new executions (it doesn't implement `Clone`), we store what is
 /// needed to construct it.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 struct TokioCommandBuilder {
     stdin: StdioClonable,
     stdout: StdioClonable,
     stderr: StdioClonable,
+    /// Whether the child process is the leader of its own process group, so
+    /// that killing it also kills the processes it spawned, e.g. the rest of a
+    /// shell pipeline.
+    own_process_group: bool,
+}
+
+impl Default for TokioCommandBuilder {
+    fn default() -> Self {
+        Self {
+            stdin: StdioClonable::default(),
+            stdout: StdioClonable::default(),
+            stderr: StdioClonable::default(),
+            own_process_group: true,
+        }
+    }
 }
 
 // TODO: this should be known at compile-time as well, not have a match statement
@@ -98,6 +127,9 @@ enum StdioClonable {
     Null,
     Piped,
     Inherit,
+    /// The controlling terminal, even if our own stdin has been redirected
+    /// (e.g. when lines are piped into watchbind).
+    Terminal,
 }
 
 impl From<&StdioClonable> for Stdio {
@@ -106,6 +138,13 @@ impl From<&StdioClonable> for Stdio {
             StdioClonable::Null => Stdio::null(),
             StdioClonable::Piped => Stdio::piped(),
             StdioClonable::Inherit => Stdio::inherit(),
+            StdioClonable::Terminal => {
+                if std::io::stdin().is_terminal() {
+                    Stdio::inherit()
+                } else {
+                    File::open("/dev/tty").map_or_else(|_| Stdio::inherit(), Stdio::from)
+                }
+            }
         }
     }
 }
@@ -119,6 +158,9 @@ impl<B, E, O, I> CommandBuilder<B, E, O, I> {
 
         CommandBuilder {
             command: self.command,
+            shell: self.shell,
+            timeout: self.timeout,
+            ok_exit_codes: self.ok_exit_codes,
             blocking: Blocking,
             output: self.output,
             interruptible: self.interruptible,
@@ -133,6 +175,9 @@ impl<B, E, O, I> CommandBuilder<B, E, O, I> {
     ) -> CommandBuilder<B, WithEnv, O, I> {
         CommandBuilder {
             command: self.command,
+            shell: self.shell,
+            timeout: self.timeout,
+            ok_exit_codes: self.ok_exit_codes,
             blocking: self.blocking,
             output: self.output,
             interruptible: self.interruptible,
@@ -147,6 +192,9 @@ impl<B, E, O, I> CommandBuilder<B, E, O, I> {
 
         CommandBuilder {
             command: self.command,
+            shell: self.shell,
+            timeout: self.timeout,
+            ok_exit_codes: self.ok_exit_codes,
             blocking: self.blocking,
             output: WithOutput,
             interruptible: self.interruptible,
@@ -157,11 +205,16 @@ impl<B, E, O, I> CommandBuilder<B, E, O, I> {
 
     pub fn inherited_io(mut self) -> CommandBuilder<B, E, InheritedIO, I> {
         // Required so child process can inherit IO from parent for TUI to work.
-        self.tokio_command.stdin = StdioClonable::Inherit;
+        self.tokio_command.stdin = StdioClonable::Terminal;
         self.tokio_command.stdout = StdioClonable::Inherit;
+        // Only the terminal's foreground process group may read from it.
+        self.tokio_command.own_process_group = false;
 
         CommandBuilder {
             command: self.command,
+            shell: self.shell,
+            timeout: self.timeout,
+            ok_exit_codes: self.ok_exit_codes,
             blocking: self.blocking,
             output: InheritedIO,
             interruptible: self.interruptible,
@@ -176,6 +229,9 @@ impl<B, E, O, I> CommandBuilder<B, E, O, I> {
     ) -> CommandBuilder<B, E, O, Interruptible> {
         CommandBuilder {
             command: self.command,
+            shell: self.shell,
+            timeout: self.timeout,
+            ok_exit_codes: self.ok_exit_codes,
             blocking: self.blocking,
             output: self.output,
             interruptible: Interruptible { interrupt_rx },
@@ -185,35 +241,72 @@ impl<B, E, O, I> CommandBuilder<B, E, O, I> {
     }
 }
 
-impl<B, O, I> CommandBuilder<B, WithoutEnv, O, I> {
-    async fn create_shell_command(&self) -> TokioCommand {
-        // TODO: optimize: save ["sh", "-c", cmd] in hashmap to avoid reallocation
-        let sh = ["sh", "-c", &self.command];
+impl<E, O, I> CommandBuilder<Blocking, E, O, I> {
+    /// Kill the command and fail if it doesn't complete within `timeout`.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
 
-        let mut command = TokioCommand::new(sh[0]);
-        command.args(&sh[1..]);
+    /// Treat the command as successful if it exits with any of the
+    /// `ok_exit_codes`.
+    pub fn ok_exit_codes(mut self, ok_exit_codes: OkExitCodes) -> Self {
+        self.ok_exit_codes = ok_exit_codes;
+        self
+    }
+}
+
+impl<B, E, O, I> CommandBuilder<B, E, O, I> {
+    /// Create the command, either executed with the shell or directly, whose
+    /// argv arguments are passed through `expand`.
+    fn create_command(&self, expand: impl Fn(&str) -> Vec<String>) -> TokioCommand {
+        let argv = self.command.argv(&self.shell, expand);
+
+        // Tokio's `Command` can't set the process group on stable, so we
+        // configure a `std::process::Command` and convert it.
+        let mut command = std::process::Command::new(&argv[0]);
+        command.args(&argv[1..]);
 
         command.stdin(&self.tokio_command.stdin);
         command.stdout(&self.tokio_command.stdout);
         command.stderr(&self.tokio_command.stderr);
 
-        command
+        #[cfg(unix)]
+        if self.tokio_command.own_process_group {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        TokioCommand::from(command)
     }
 }
 
-impl<B, O, I> CommandBuilder<B, WithEnv, O, I> {
+impl<B, E, O, I> CommandBuilder<B, E, O, I> {
+    /// Spawn the created `command` as a child process.
+    fn spawn(&self, mut command: TokioCommand) -> Result<Child> {
+        let child = command.spawn().map_err(|source| SpawnError {
+            command: self.command.to_string(),
+            source,
+        })?;
+        Ok(child)
+    }
+}
+
+impl<B, O, I> CommandBuilder<B, WithoutEnv, O, I> {
     async fn create_shell_command(&self) -> TokioCommand {
-        // TODO: optimize: save ["sh", "-c", cmd] in hashmap to avoid reallocation
-        let sh = ["sh", "-c", &self.command];
+        self.create_command(|arg| vec![arg.to_owned()])
+    }
+}
 
-        let mut command = TokioCommand::new(sh[0]);
-        command.args(&sh[1..]);
+impl<B, O, I> CommandBuilder<B, WithEnv, O, I> {
+    async fn create_shell_command(&self) -> TokioCommand {
+        let env_variables = self.env.env_variables.lock().await;
 
-        command.stdin(&self.tokio_command.stdin);
-        command.stdout(&self.tokio_command.stdout);
-        command.stderr(&self.tokio_command.stderr);
+        // Without a shell, we have to expand references to our environment
+        // variables in argv arguments ourselves.
+        let mut command = self.create_command(|arg| env_variables.expand_argument(arg));
 
-        let env_variables: HashMap<_, _> = self.env.env_variables.lock().await.deref().into();
+        let env_variables: HashMap<_, _> = env_variables.deref().into();
         command.envs(env_variables);
 
         command
@@ -225,37 +318,45 @@ impl<B, O, I> CommandBuilder<B, WithEnv, O, I> {
 
 impl CommandBuilder<NonBlocking, WithoutEnv, NoOutput, NonInterruptible> {
     pub async fn execute(&self) -> Result<()> {
-        self.create_shell_command().await.spawn()?;
+        self.spawn(self.create_shell_command().await)?;
         Ok(())
     }
 }
 
 impl CommandBuilder<NonBlocking, WithEnv, NoOutput, NonInterruptible> {
     pub async fn execute(&self) -> Result<()> {
-        self.create_shell_command().await.spawn()?;
+        self.spawn(self.create_shell_command().await)?;
         Ok(())
     }
 }
 
 impl CommandBuilder<Blocking, WithEnv, NoOutput, NonInterruptible> {
-    pub async fn execute(&self) -> Result<()> {
-        let mut child = self.create_shell_command().await.spawn()?;
-
-        let exit_status = child.wait().await?;
-        self.assert_child_exited_successfully(exit_status, &mut child.stderr)
-            .await?;
-
-        Ok(())
+    /// Execute the command, but kill it once an interrupt signal is received
+    /// through `interrupt_rx`, in which case `None` is returned.
+    pub async fn execute_interruptible(
+        &self,
+        interrupt_rx: &mut Receiver<InterruptSignal>,
+    ) -> Result<Option<()>> {
+        let mut child = self.spawn(self.create_shell_command().await)?;
+
+        let Some(output) =
+            wait_for_child_with_output(&mut child, &self.command, self.timeout, Some(interrupt_rx))
+                .await?
+        else {
+            return Ok(None);
+        };
+        self.assert_child_exited_successfully(output.exit_status, output.stderr)?;
+
+        Ok(Some(()))
     }
 }
 
 impl CommandBuilder<Blocking, WithEnv, InheritedIO, NonInterruptible> {
     pub async fn execute(&self) -> Result<()> {
-        let mut child = self.create_shell_command().await.spawn()?;
+        let mut child = self.spawn(self.create_shell_command().await)?;
 
-        let exit_status = child.wait().await?;
-        self.assert_child_exited_successfully(exit_status, &mut child.stderr)
-            .await?;
+        let output = self.wait_within_timeout(&mut child).await?;
+        self.assert_child_exited_successfully(output.exit_status, output.stderr)?;
 
         Ok(())
     }
@@ -263,43 +364,116 @@ impl CommandBuilder<Blocking, WithEnv, InheritedIO, NonInterruptible> {
 
 impl CommandBuilder<Blocking, WithoutEnv, WithOutput, NonInterruptible> {
     pub async fn execute(&self) -> Result<String> {
-        let mut child = self.create_shell_command().await.spawn()?;
-
-        let exit_status = child.wait().await?;
-        self.assert_child_exited_successfully(exit_status, &mut child.stderr)
-            .await?;
+        let mut child = self.spawn(self.create_shell_command().await)?;
 
-        // Read stdout.
-        let mut stdout = String::new();
-        child.stdout.unwrap().read_to_string(&mut stdout).await?;
+        let output = self.wait_within_timeout(&mut child).await?;
+        self.assert_child_exited_successfully(output.exit_status, output.stderr)?;
 
-        Ok(stdout)
+        Ok(output.stdout.expect("stdout is piped"))
     }
 }
 
 impl CommandBuilder<Blocking, WithEnv, WithOutput, NonInterruptible> {
     pub async fn execute(&self) -> Result<String> {
-        let mut child = self.create_shell_command().await.spawn()?;
+        let output = self.execute_until_interrupted(None).await?;
+        Ok(output
+            .expect("can't be interrupted without an interrupt receiver")
+            .stdout)
+    }
 
-        let exit_status = child.wait().await?;
-        self.assert_child_exited_successfully(exit_status, &mut child.stderr)
-            .await?;
+    /// Execute the command, but kill it once an interrupt signal is received
+    /// through `interrupt_rx`, in which case `None` is returned.
+    pub async fn execute_interruptible(
+        &self,
+        interrupt_rx: &mut Receiver<InterruptSignal>,
+    ) -> Result<Option<String>> {
+        let output = self.execute_until_interrupted(Some(interrupt_rx)).await?;
+        Ok(output.map(|output| output.stdout))
+    }
 
-        // Read stdout.
-        let mut stdout = String::new();
-        child.stdout.unwrap().read_to_string(&mut stdout).await?;
+    /// Execute the command like `execute_interruptible`, but also return its
+    /// stderr, which is otherwise only reported if the command fails.
+    pub async fn execute_interruptible_with_stderr(
+        &self,
+        interrupt_rx: &mut Receiver<InterruptSignal>,
+    ) -> Result<Option<SubcommandOutput>> {
+        self.execute_until_interrupted(Some(interrupt_rx)).await
+    }
 
-        Ok(stdout)
+    async fn execute_until_interrupted(
+        &self,
+        interrupt_rx: Option<&mut Receiver<InterruptSignal>>,
+    ) -> Result<Option<SubcommandOutput>> {
+        let mut child = self.spawn(self.create_shell_command().await)?;
+
+        let Some(output) =
+            wait_for_child_with_output(&mut child, &self.command, self.timeout, interrupt_rx)
+                .await?
+        else {
+            return Ok(None);
+        };
+        let stderr = output.stderr.expect("stderr is piped");
+        self.assert_child_exited_successfully(output.exit_status, Some(stderr.clone()))?;
+
+        Ok(Some(SubcommandOutput {
+            stdout: output.stdout.expect("stdout is piped"),
+            stderr,
+        }))
     }
 }
 
-/// Encodes whether a command's execution was interrupted, or the stdout if it
-/// ran to completion.
+/// The stdout and stderr of a subcommand that completed successfully.
+pub struct SubcommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Encodes whether a command's execution was interrupted, or the stdout and
+/// exit code if it ran to completion.
 pub enum ExecutionResult {
-    Stdout(String),
+    Stdout {
+        stdout: String,
+        exit_code: Option<i32>,
+    },
     Interrupted,
 }
 
+/// A child process whose stdout is read line by line while it is running.
+pub struct StreamingChild {
+    child: Child,
+    stdout: Lines<BufReader<ChildStdout>>,
+    /// Reads stderr while the child process is running, since it would
+    /// otherwise block once the pipe's buffer is full, and returns its tail.
+    stderr_tail: JoinHandle<Vec<u8>>,
+}
+
+/// The maximum number of bytes at the end of a streaming child process's
+/// stderr that are kept to report them if it fails.
+const STREAMING_STDERR_TAIL_BYTES: usize = 64 * 1024;
+
+impl StreamingChild {
+    /// Read the next line from stdout, or `None` if stdout has been closed.
+    /// Cancel-safe, so it can be used in `tokio::select!`.
+    pub async fn next_line(&mut self) -> Result<Option<String>> {
+        Ok(self.stdout.next_line().await?)
+    }
+
+    /// Kill the child process and the processes it spawned.
+    pub async fn kill(&mut self) -> Result<()> {
+        kill_process_group(&mut self.child).await
+    }
+}
+
+impl Drop for StreamingChild {
+    fn drop(&mut self) {
+        // The command may run indefinitely, so make sure neither it nor the
+        // processes it spawned outlive it. The child process itself is
+        // killed on drop, but not the rest of its process group.
+        start_killing_process_group(&self.child);
+        self.stderr_tail.abort();
+    }
+}
+
 // TODO: find better name
 /// The async call was woken up due to this reason.
 pub enum WasWoken {
@@ -309,24 +483,55 @@ pub enum WasWoken {
 
 impl CommandBuilder<Blocking, WithEnv, WithOutput, Interruptible> {
     pub async fn execute(&mut self) -> Result<ExecutionResult> {
-        let mut child = self.create_shell_command().await.spawn()?;
-
-        tokio::select! {
-            _ = self.interruptible.interrupt_rx.recv() => {
-                child.kill().await?;
-                Ok(ExecutionResult::Interrupted)
-            },
-            exit_status = child.wait() => {
-                self.assert_child_exited_successfully(exit_status?, &mut child.stderr).await?;
-
-                // Read stdout.
-                let mut stdout = String::new();
-                // TODO: remove unwrap()
-                child.stdout.unwrap().read_to_string(&mut stdout).await?;
-
-                Ok(ExecutionResult::Stdout(stdout))
-            }
-        }
+        let mut child = self.spawn(self.create_shell_command().await)?;
+
+        let Some(output) = wait_for_child_with_output(
+            &mut child,
+            &self.command,
+            self.timeout,
+            Some(&mut self.interruptible.interrupt_rx),
+        )
+        .await?
+        else {
+            return Ok(ExecutionResult::Interrupted);
+        };
+        let exit_code = self.assert_child_exited_successfully(output.exit_status, output.stderr)?;
+
+        Ok(ExecutionResult::Stdout {
+            stdout: output.stdout.expect("stdout is piped"),
+            exit_code,
+        })
+    }
+}
+
+impl CommandBuilder<Blocking, WithEnv, WithOutput, Interruptible> {
+    /// Spawn the command without waiting for it to complete, so that its
+    /// stdout can be streamed while it is running.
+    pub async fn spawn_streaming(&self) -> Result<StreamingChild> {
+        // The command may run indefinitely, so make sure it doesn't outlive
+        // watchbind.
+        let mut command = self.create_shell_command().await;
+        command.kill_on_drop(true);
+        let mut child = self.spawn(command)?;
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let stderr_tail = tokio::spawn(read_stderr_tail(stderr));
+        Ok(StreamingChild {
+            child,
+            stdout,
+            stderr_tail,
+        })
+    }
+
+    /// Wait for a streaming child process to exit, once its stdout has been
+    /// closed, and return its exit code.
+    pub async fn wait_streaming(&self, mut child: StreamingChild) -> Result<Option<i32>> {
+        let exit_status = child.child.wait().await?;
+        let stderr = (&mut child.stderr_tail).await?;
+        self.assert_child_exited_successfully(
+            exit_status,
+            Some(String::from_utf8_lossy(&stderr).into_owned()),
+        )
     }
 }
 
@@ -349,44 +554,385 @@ impl<B, E, O> CommandBuilder<B, E, O, Interruptible> {
 }
 
 impl<B, E, O, I> CommandBuilder<B, E, O, I> {
+    /// Wait for the child process to exit and return its output. If it
+    /// doesn't exit within the timeout, it is killed and an error is returned.
+    async fn wait_within_timeout(&self, child: &mut Child) -> Result<ChildOutput> {
+        let output = wait_for_child_with_output(child, &self.command, self.timeout, None).await?;
+        Ok(output.expect("can't be interrupted without an interrupt receiver"))
+    }
+
     /// Return an error in case the exit status/exit code indicates failure, and
-    /// include stderr in error message.
-    async fn assert_child_exited_successfully(
+    /// include stderr in error message. Otherwise, return the exit code.
+    fn assert_child_exited_successfully(
         &self,
         exit_status: ExitStatus,
-        stderr: &mut Option<tokio::process::ChildStderr>,
-    ) -> Result<()> {
-        if !exit_status.success() {
-            // Read exit code.
-            let status_code_str = match exit_status.code() {
-                Some(code) => Cow::Owned(format!("status code {}", code)),
-                None => Cow::Borrowed("unknown status code"),
-            };
-
-            let stderr_str = match stderr {
-                Some(stderr) => {
-                    // Read stderr.
-                    let mut stderr_str = String::new();
-                    stderr.read_to_string(&mut stderr_str).await?;
-                    Cow::Owned(format!("--STDERR--\n{}\n----------", stderr_str))
+        stderr: Option<String>,
+    ) -> Result<Option<i32>> {
+        if !self.ok_exit_codes.is_ok(exit_status) {
+            return Err(ExitError {
+                command: self.command.to_string(),
+                exit_code: exit_status.code(),
+                stderr,
+            }
+            .into());
+        }
+        Ok(exit_status.code())
+    }
+}
+
+/// The error of a command that exited with an exit code that isn't treated as
+/// success.
+#[derive(Debug)]
+pub struct ExitError {
+    command: String,
+    exit_code: Option<i32>,
+    stderr: Option<String>,
+}
+
+impl ExitError {
+    /// Get the exit code of the command that failed with `error`, if it
+    /// exited with one.
+    pub fn exit_code_of(error: &anyhow::Error) -> Option<i32> {
+        error
+            .downcast_ref::<Self>()
+            .and_then(|exit_error| exit_error.exit_code)
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_code_str = match self.exit_code {
+            Some(code) => Cow::Owned(format!("status code {}", code)),
+            None => Cow::Borrowed("unknown status code"),
+        };
+        let stderr_str = match &self.stderr {
+            Some(stderr) => Cow::Owned(format!("--STDERR--\n{}\n----------", stderr)),
+            None => Cow::Borrowed("unknown STDERR"),
+        };
+        write!(
+            f,
+            "Process \"{}\" exited with {}:\n{}",
+            self.command, status_code_str, stderr_str
+        )
+    }
+}
+
+impl std::error::Error for ExitError {}
+
+/// The error of a command that couldn't be spawned, e.g. because the program
+/// doesn't exist.
+#[derive(Debug)]
+pub struct SpawnError {
+    command: String,
+    source: std::io::Error,
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Process \"{}\" failed to spawn", self.command)
+    }
+}
+
+impl std::error::Error for SpawnError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// The error of a command that didn't complete within its timeout and was
+/// killed.
+#[derive(Debug)]
+pub struct TimeoutError {
+    command: String,
+    timeout: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Process \"{}\" timed out after {:?} and was killed",
+            self.command, self.timeout
+        )
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// Whether the `error` is caused by a command that failed to spawn, exited
+/// unsuccessfully or timed out, as opposed to e.g. an internal error.
+pub fn is_command_failure(error: &anyhow::Error) -> bool {
+    error.is::<ExitError>() || error.is::<SpawnError>() || error.is::<TimeoutError>()
+}
+
+/// Wait for the child process of the `command` to exit. The child process is
+/// killed if it doesn't exit within the `timeout`, in which case an error is
+/// returned, or once an interrupt signal is received through `interrupt_rx`,
+/// in which case `None` is returned.
+async fn wait_for_child(
+    child: &mut Child,
+    command: &CommandLine,
+    timeout: Option<Duration>,
+    interrupt_rx: Option<&mut Receiver<InterruptSignal>>,
+) -> Result<Option<ExitStatus>> {
+    let interrupted = async {
+        match interrupt_rx {
+            Some(interrupt_rx) => interrupt_rx.recv().await,
+            None => std::future::pending().await,
+        }
+    };
+    let timed_out = async {
+        match timeout {
+            Some(timeout) => {
+                tokio::time::sleep(timeout).await;
+                timeout
+            }
+            None => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        _ = interrupted => {
+            kill_process_group(child).await?;
+            Ok(None)
+        },
+        timeout = timed_out => {
+            kill_process_group(child).await?;
+            Err(TimeoutError {
+                command: command.to_string(),
+                timeout,
+            }
+            .into())
+        },
+        exit_status = child.wait() => Ok(Some(exit_status?)),
+    }
+}
+
+/// Kill the child process along with all other processes in its process group,
+/// if it leads one, which would otherwise keep running, e.g. the rest of a
+/// shell pipeline.
+async fn kill_process_group(child: &mut Child) -> Result<()> {
+    start_killing_process_group(child);
+    child.kill().await?;
+    Ok(())
+}
+
+/// Send a kill signal to the process group led by the child process, if it
+/// leads one and is still running, without waiting for it to exit.
+fn start_killing_process_group(child: &Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: `killpg` has no memory safety requirements. If the child
+        // process doesn't lead a process group, it simply fails.
+        unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+    }
+}
+
+/// The exit status of a child process that has exited, along with its stdout
+/// and stderr, if they are piped.
+struct ChildOutput {
+    exit_status: ExitStatus,
+    stdout: Option<String>,
+    stderr: Option<String>,
+}
+
+/// Wait for the child process of the `command` to exit like `wait_for_child`,
+/// and return its exit status, stdout and stderr. Stdout and stderr are read
+/// while waiting, since the child process would otherwise block once a pipe's
+/// buffer is full, e.g. for long outputs.
+async fn wait_for_child_with_output(
+    child: &mut Child,
+    command: &CommandLine,
+    timeout: Option<Duration>,
+    interrupt_rx: Option<&mut Receiver<InterruptSignal>>,
+) -> Result<Option<ChildOutput>> {
+    let stdout_pipe = child.stdout.take();
+    let stderr_pipe = child.stderr.take();
+    let (stdout_piped, stderr_piped) = (stdout_pipe.is_some(), stderr_pipe.is_some());
+
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let exit_status = {
+        let read_stdout = read_pipe(stdout_pipe, &mut stdout);
+        let read_stderr = read_pipe(stderr_pipe, &mut stderr);
+        tokio::pin!(read_stdout, read_stderr);
+        let mut stdout_closed = false;
+        let mut stderr_closed = false;
+
+        let wait = wait_for_child(child, command, timeout, interrupt_rx);
+        tokio::pin!(wait);
+        let exit_status = loop {
+            tokio::select! {
+                result = &mut read_stdout, if !stdout_closed => {
+                    result?;
+                    stdout_closed = true;
+                }
+                result = &mut read_stderr, if !stderr_closed => {
+                    result?;
+                    stderr_closed = true;
                 }
-                None => Cow::Borrowed("unknown STDERR"),
-            };
-            bail!(
-                "Process \"{}\" exited with {}:\n{}",
-                self.command,
-                status_code_str,
-                stderr_str
-            );
+                exit_status = &mut wait => break exit_status?,
+            }
+        };
+        if exit_status.is_some() {
+            if !stdout_closed {
+                read_stdout.await?;
+            }
+            if !stderr_closed {
+                read_stderr.await?;
+            }
         }
-        Ok(())
+        exit_status
+    };
+
+    let Some(exit_status) = exit_status else {
+        return Ok(None);
+    };
+    Ok(Some(ChildOutput {
+        exit_status,
+        stdout: stdout_piped
+            .then(|| String::from_utf8(stdout))
+            .transpose()?,
+        // Stderr is only displayed, so it doesn't have to be valid UTF-8.
+        stderr: stderr_piped.then(|| String::from_utf8_lossy(&stderr).into_owned()),
+    }))
+}
+
+/// Read the `stderr` of a streaming child process until it is closed, keeping
+/// only its last `STREAMING_STDERR_TAIL_BYTES` bytes, since the process may
+/// run indefinitely.
+async fn read_stderr_tail(mut stderr: ChildStderr) -> Vec<u8> {
+    let mut tail = VecDeque::new();
+    let mut chunk = [0; 8192];
+    // A failure to read stderr only leaves the reported tail incomplete.
+    while let Ok(len @ 1..) = stderr.read(&mut chunk).await {
+        tail.extend(&chunk[..len]);
+        let excess = tail.len().saturating_sub(STREAMING_STDERR_TAIL_BYTES);
+        tail.drain(..excess);
     }
+    tail.into()
+}
+
+/// Read the `pipe` until it is closed, if there is one, and append its
+/// content to `buf`.
+async fn read_pipe(pipe: Option<impl AsyncRead + Unpin>, buf: &mut Vec<u8>) -> Result<()> {
+    if let Some(mut pipe) = pipe {
+        pipe.read_to_end(buf).await?;
+    }
+    Ok(())
 }
 
 // TODO: update tests
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use std::time::Instant;
+
+    fn spawn(program: &str, args: &[&str]) -> Child {
+        TokioCommand::new(program)
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_child_timeout() {
+        let command = CommandLine::Shell("sleep 10".to_owned());
+        let mut child = spawn("sleep", &["10"]);
+
+        let start = Instant::now();
+        let result = wait_for_child(&mut child, &command, Some(Duration::from_millis(50)), None);
+        assert!(result.await.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(child.try_wait().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_child_interrupt() {
+        let command = CommandLine::Shell("sleep 10".to_owned());
+        let mut child = spawn("sleep", &["10"]);
+        let (interrupt_tx, mut interrupt_rx) = tokio::sync::mpsc::channel(1);
+        interrupt_tx.send(InterruptSignal).await.unwrap();
+
+        let start = Instant::now();
+        let result = wait_for_child(&mut child, &command, None, Some(&mut interrupt_rx));
+        assert!(result.await.unwrap().is_none());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(child.try_wait().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_whole_pipeline() {
+        let file = std::env::temp_dir().join(format!("watchbind-test-{}", std::process::id()));
+        let command = format!("(sleep 0.5; touch {}) | cat", file.display());
+        let command = CommandBuilder::new(CommandLine::Shell(command), "sh -c".parse().unwrap())
+            .blocking()
+            .with_output()
+            .timeout(Some(Duration::from_millis(100)));
+
+        assert!(command.execute().await.is_err());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_child_with_long_stdout() {
+        // Exceeds the pipe's buffer, so the child process only exits if its
+        // stdout is read while waiting.
+        let command = CommandLine::Shell("head -c 1000000 /dev/zero".to_owned());
+        let mut child = spawn("head", &["-c", "1000000", "/dev/zero"]);
+
+        let result =
+            wait_for_child_with_output(&mut child, &command, Some(Duration::from_secs(5)), None);
+        let output = result.await.unwrap().unwrap();
+        assert!(output.exit_status.success());
+        assert_eq!(output.stdout.unwrap().len(), 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_with_long_stderr() {
+        // Exceeds the pipe's buffer, so stdout is only streamed if stderr is
+        // read concurrently.
+        let command =
+            CommandLine::Shell("head -c 1000000 /dev/zero >&2; echo line; exit 1".to_owned());
+        let (_interrupt_tx, interrupt_rx) = tokio::sync::mpsc::channel(1);
+        let command = CommandBuilder::new(command, "sh -c".parse().unwrap())
+            .blocking()
+            .with_output()
+            .with_env(Arc::new(Mutex::new(EnvVariables::default())))
+            .interruptible(interrupt_rx);
+
+        let mut child = command.spawn_streaming().await.unwrap();
+        let line = tokio::time::timeout(Duration::from_secs(5), child.next_line());
+        assert_eq!(line.await.unwrap().unwrap().as_deref(), Some("line"));
+        assert_eq!(child.next_line().await.unwrap(), None);
+
+        let error = command.wait_streaming(child).await.unwrap_err();
+        let exit_error = error.downcast_ref::<ExitError>().unwrap();
+        assert_eq!(
+            exit_error.stderr.as_ref().unwrap().len(),
+            STREAMING_STDERR_TAIL_BYTES
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_child_with_long_stderr() {
+        // Exceeds the pipe's buffer, so the child process only exits if its
+        // stderr is read while waiting.
+        let command = CommandBuilder::new(
+            CommandLine::Shell("head -c 1000000 /dev/zero >&2; exit 1".to_owned()),
+            "sh -c".parse().unwrap(),
+        )
+        .blocking()
+        .with_output()
+        .timeout(Some(Duration::from_secs(5)));
+
+        let error = command.execute().await.unwrap_err();
+        let exit_error = error.downcast_ref::<ExitError>().unwrap();
+        assert_eq!(exit_error.exit_code, Some(1));
+        assert_eq!(exit_error.stderr.as_ref().unwrap().len(), 1_000_000);
+    }
 
     // #[test]
     // fn test_executing_echo_command() -> Result<()> {